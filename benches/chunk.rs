@@ -0,0 +1,160 @@
+use blocks::block::{Block, BlockRegistry};
+use blocks::chunk::{Chunk, Neighbours, CHUNK_SIZE};
+use blocks::chunk_blocks::ChunkBlocks;
+use blocks::generator::Generator;
+use blocks::mesh::Mesh;
+use blocks::seed::WorldSeed;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+// blocks along chunk edge
+const SIZE: usize = CHUNK_SIZE as usize;
+
+// blocks per chunk
+const LEN: usize = SIZE * SIZE * SIZE;
+
+// terrain-like block at local position
+fn terrain(x: usize, y: usize, z: usize) -> Block {
+    let height = 6 + (x * 7 + z * 3) % 5;
+
+    match y.cmp(&height) {
+        std::cmp::Ordering::Less  => Block(2),
+        std::cmp::Ordering::Equal => Block(1),
+        _ => Block::AIR,
+    }
+}
+
+// nested vectors in z, x, y order
+fn nested() -> Vec<Vec<Vec<Block>>> {
+    (0..SIZE).map(|z| {
+        (0..SIZE).map(|x| (0..SIZE).map(|y| terrain(x, y, z)).collect()).collect()
+    }).collect()
+}
+
+// flat array in z, x, y order
+fn flat() -> Vec<Block> {
+    itertools::iproduct!(0..SIZE, 0..SIZE, 0..SIZE).map(|(z, x, y)| terrain(x, y, z)).collect()
+}
+
+// palette storage in z, x, y order
+fn paletted() -> ChunkBlocks {
+    itertools::iproduct!(0..SIZE, 0..SIZE, 0..SIZE).map(|(z, x, y)| terrain(x, y, z)).collect()
+}
+
+// positions in z, x, y order
+fn positions() -> impl Iterator<Item = (usize, usize, usize)> {
+    itertools::iproduct!(0..SIZE, 0..SIZE, 0..SIZE)
+}
+
+// pseudo random indices
+fn scattered() -> Vec<usize> {
+    let mut state = 7u32;
+
+    (0..LEN).map(|_| {
+        state = state.wrapping_mul(1103515245).wrapping_add(12345);
+        (state >> 8) as usize % LEN
+    }).collect()
+}
+
+// read every block
+fn read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read");
+
+    let blocks = nested();
+    group.bench_function("nested", |b| {
+        b.iter(|| positions().filter(|&(z, x, y)| !blocks[z][x][y].is_air()).count())
+    });
+
+    let blocks = flat();
+    group.bench_function("flat", |b| {
+        b.iter(|| blocks.iter().filter(|block| !block.is_air()).count())
+    });
+
+    let blocks = paletted();
+    group.bench_function("paletted", |b| {
+        b.iter(|| blocks.iter().filter(|block| !block.is_air()).count())
+    });
+
+    group.bench_function("paletted_indexed", |b| {
+        b.iter(|| (0..LEN).filter(|&index| !blocks.get(index).is_air()).count())
+    });
+
+    group.finish();
+}
+
+// write blocks at scattered positions
+fn write(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write");
+    let indices   = scattered();
+
+    group.bench_function("nested", |b| {
+        let mut blocks = nested();
+
+        b.iter(|| {
+            for &index in &indices {
+                blocks[index / (SIZE * SIZE)][index / SIZE % SIZE][index % SIZE] = Block(3);
+            }
+        })
+    });
+
+    group.bench_function("flat", |b| {
+        let mut blocks = flat();
+
+        b.iter(|| {
+            for &index in &indices {
+                blocks[index] = Block(3);
+            }
+        })
+    });
+
+    group.bench_function("paletted", |b| {
+        let mut blocks = paletted();
+
+        b.iter(|| {
+            for &index in &indices {
+                blocks.set(index, Block(3));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+// build terrain chunk and report its memory
+fn build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build");
+
+    group.bench_function("nested", |b| b.iter(|| black_box(nested())));
+    group.bench_function("flat", |b| b.iter(|| black_box(flat())));
+    group.bench_function("paletted", |b| b.iter(|| black_box(paletted())));
+
+    group.finish();
+
+    // vector headers of every row and column
+    let headers = (1 + SIZE + SIZE * SIZE) * std::mem::size_of::<Vec<Block>>();
+    let blocks  = LEN * std::mem::size_of::<Block>();
+
+    println!(
+        "chunk memory: nested {} B, flat {} B, paletted {} B",
+        headers + blocks,
+        blocks,
+        paletted().memory(),
+    );
+}
+
+// generate and mesh real chunks
+fn chunk(c: &mut Criterion) {
+    let registry  = BlockRegistry::builtin();
+    let generator = Generator::new(WorldSeed::new(1), &registry).unwrap();
+
+    c.bench_function("generate", |b| b.iter(|| Chunk::new(0, 0, 0, &generator)));
+
+    let chunk      = Chunk::new(0, 0, 0, &generator);
+    let neighbours = Neighbours::default();
+
+    c.bench_function("instances", |b| b.iter(|| chunk.instances(&neighbours, &registry)));
+    c.bench_function("greedy", |b| b.iter(|| Mesh::greedy(&chunk, &neighbours, &registry)));
+}
+
+criterion_group!(benches, read, write, build, chunk);
+criterion_main!(benches);
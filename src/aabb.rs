@@ -0,0 +1,66 @@
+use cgmath::{Point3, Vector3};
+
+// collision tolerance
+const EPSILON: f32 = 1e-4;
+
+// axis aligned box
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    // corners
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+    // create box
+    pub fn new(min: Point3<f32>, max: Point3<f32>) -> Self {
+        Self { min, max }
+    }
+
+    // box of block at position
+    pub fn block(pos: [i32; 3]) -> Self {
+        let min = Point3::new(pos[0] as f32, pos[1] as f32, pos[2] as f32);
+        Self::new(min, min + cgmath::vec3(1.0, 1.0, 1.0))
+    }
+
+    // moved box
+    pub fn translate(&self, offset: Vector3<f32>) -> Self {
+        Self::new(self.min + offset, self.max + offset)
+    }
+
+    // box covering both boxes
+    pub fn union(&self, other: &Aabb) -> Self {
+        Self::new(
+            Point3::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y), self.min.z.min(other.min.z)),
+            Point3::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y), self.max.z.max(other.max.z)),
+        )
+    }
+
+    // limit movement along axis before hitting other box
+    pub fn clip(&self, other: &Aabb, axis: usize, dist: f32) -> f32 {
+        // only boxes in the way
+        let blocking = (0..3).filter(|&other_axis| other_axis != axis).all(|other_axis| {
+            self.min[other_axis] < other.max[other_axis] - EPSILON &&
+                other.min[other_axis] < self.max[other_axis] - EPSILON
+        });
+
+        if !blocking {
+            return dist;
+        }
+
+        if dist > 0.0 && self.max[axis] <= other.min[axis] + EPSILON {
+            dist.min(other.min[axis] - self.max[axis])
+        } else if dist < 0.0 && self.min[axis] >= other.max[axis] - EPSILON {
+            dist.max(other.max[axis] - self.min[axis])
+        } else {
+            dist
+        }
+    }
+
+    // check overlap, ignoring touching sides
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x < other.max.x && other.min.x < self.max.x &&
+            self.min.y < other.max.y && other.min.y < self.max.y &&
+            self.min.z < other.max.z && other.min.z < self.max.z
+    }
+}
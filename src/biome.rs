@@ -0,0 +1,110 @@
+// terrain biome
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Biome {
+    Ocean,
+    Plains,
+    Desert,
+    Mountains,
+    Snow,
+}
+
+// terrain shape of biome
+#[derive(Copy, Clone, Debug)]
+pub struct HeightCurve {
+    // lowest surface
+    pub base: i32,
+
+    // noise scale before curve
+    pub amplitude: f64,
+
+    // curve exponent, above one sharpens peaks
+    pub exponent: f64,
+}
+
+impl HeightCurve {
+    // surface height from raw noise in -1..1
+    pub fn height(&self, raw: f64) -> f64 {
+        self.base as f64 + ((raw + 1.0).max(0.0) * self.amplitude).powf(self.exponent)
+    }
+}
+
+// surface block names and depth of biome
+#[derive(Copy, Clone, Debug)]
+pub struct Layers {
+    // top block
+    pub surface: &'static str,
+
+    // blocks below top
+    pub filler: &'static str,
+
+    // filler depth before stone
+    pub depth: i32,
+}
+
+impl Biome {
+    // every biome
+    pub const ALL: [Biome; 5] = [Biome::Ocean, Biome::Plains, Biome::Desert, Biome::Mountains, Biome::Snow];
+
+    // temperature and humidity the biome is centered on
+    pub fn climate(&self) -> [f64; 2] {
+        match self {
+            Biome::Ocean     => [ 0.0,  0.8],
+            Biome::Plains    => [ 0.0,  0.0],
+            Biome::Desert    => [ 0.7, -0.5],
+            Biome::Mountains => [-0.2, -0.7],
+            Biome::Snow      => [-0.8,  0.0],
+        }
+    }
+
+    // terrain shape
+    pub fn curve(&self) -> HeightCurve {
+        match self {
+            Biome::Ocean     => HeightCurve { base: -30, amplitude:  8.0, exponent: 1.0 },
+            Biome::Plains    => HeightCurve { base:  -8, amplitude: 15.0, exponent: 0.9 },
+            Biome::Desert    => HeightCurve { base:  -6, amplitude: 10.0, exponent: 0.9 },
+            Biome::Mountains => HeightCurve { base:  -8, amplitude: 22.0, exponent: 1.2 },
+            Biome::Snow      => HeightCurve { base:  -4, amplitude: 15.0, exponent: 1.0 },
+        }
+    }
+
+    // surface and filler blocks
+    pub fn layers(&self) -> Layers {
+        match self {
+            Biome::Ocean     => Layers { surface: "sand",  filler: "sand",  depth: 3 },
+            Biome::Plains    => Layers { surface: "grass", filler: "dirt",  depth: 3 },
+            Biome::Desert    => Layers { surface: "sand",  filler: "sand",  depth: 4 },
+            Biome::Mountains => Layers { surface: "stone", filler: "stone", depth: 1 },
+            Biome::Snow      => Layers { surface: "snow",  filler: "dirt",  depth: 3 },
+        }
+    }
+
+    // chance of a tree per attempt
+    pub fn trees(&self) -> f64 {
+        match self {
+            Biome::Plains => 0.6,
+            Biome::Snow   => 0.4,
+            _             => 0.0,
+        }
+    }
+
+    // chance of a boulder per attempt
+    pub fn boulders(&self) -> f64 {
+        match self {
+            Biome::Mountains => 0.5,
+            Biome::Plains    => 0.1,
+            Biome::Desert    => 0.1,
+            _                => 0.0,
+        }
+    }
+
+    // display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Biome::Ocean     => "ocean",
+            Biome::Plains    => "plains",
+            Biome::Desert    => "desert",
+            Biome::Mountains => "mountains",
+            Biome::Snow      => "snow",
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use crate::vertex::{ATLAS_COLS, ATLAS_ROWS};
+
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, Context, bail, ensure};
+
+use std::collections::HashMap;
+
+// builtin definitions
+const BUILTIN: &str = include_str!("assets/blocks.json");
+
+// fluid levels, sources being the highest
+pub const FLUID_LEVELS: u8 = 8;
+
+// block id
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Block(pub u16);
+
+impl Block {
+    // empty block
+    pub const AIR: Self = Self(0);
+
+    // check if air
+    pub fn is_air(&self) -> bool {
+        *self == Self::AIR
+    }
+}
+
+// face texture tiles as atlas column and row
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Textures {
+    // every face
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub all: Option<[u16; 2]>,
+
+    // front, back, left and right
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub side: Option<[u16; 2]>,
+
+    // front and back
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub front: Option<[u16; 2]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub back:  Option<[u16; 2]>,
+
+    // left and right
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub left:  Option<[u16; 2]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub right: Option<[u16; 2]>,
+
+    // top and bottom
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top:    Option<[u16; 2]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bottom: Option<[u16; 2]>,
+}
+
+impl Textures {
+    // same tile on every face
+    pub fn all(tile: [u16; 2]) -> Self {
+        Self {
+            all: Some(tile),
+            ..Default::default()
+        }
+    }
+
+    // tile for face, most specific first
+    pub fn face(&self, face: usize) -> Option<[u16; 2]> {
+        let specific = match face {
+            0 => self.front,
+            1 => self.back,
+            2 => self.left,
+            3 => self.right,
+            4 => self.top,
+            _ => self.bottom,
+        };
+
+        // side covers horizontal faces
+        let side = if face < 4 { self.side } else { None };
+
+        specific.or(side).or(self.all)
+    }
+}
+
+// block definition
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlockDef {
+    // unique name
+    pub name: String,
+
+    // face texture tiles
+    #[serde(default)]
+    pub textures: Textures,
+
+    // collides with player
+    #[serde(default = "default_true")]
+    pub solid: bool,
+
+    // shows faces behind it
+    #[serde(default)]
+    pub transparent: bool,
+
+    // drawn blended after opaque blocks
+    #[serde(default)]
+    pub translucent: bool,
+
+    // emitted light level
+    #[serde(default)]
+    pub emissive: u8,
+
+    // time to break
+    #[serde(default = "default_hardness")]
+    pub hardness: f32,
+
+    // flows into neighbouring air
+    #[serde(default)]
+    pub fluid: bool,
+}
+
+impl BlockDef {
+    // create solid block
+    pub fn new(name: &str, textures: Textures) -> Self {
+        Self {
+            textures,
+            name:        name.to_string(),
+            solid:       true,
+            transparent: false,
+            translucent: false,
+            emissive:    0,
+            hardness:    default_hardness(),
+            fluid:       false,
+        }
+    }
+
+    // check if drawn in the blended pass, as fluids always are
+    pub fn blended(&self) -> bool {
+        self.translucent || self.fluid
+    }
+
+    // atlas tile index for face
+    pub fn tile(&self, face: usize) -> u16 {
+        self.textures
+            .face(face)
+            .map_or(0, |[col, row]| row * ATLAS_COLS + col)
+    }
+
+    // atlas tile indices for all faces
+    pub fn tiles(&self) -> [u16; 6] {
+        [0, 1, 2, 3, 4, 5].map(|face| self.tile(face))
+    }
+}
+
+// serde default for flags
+fn default_true() -> bool {
+    true
+}
+
+// serde default for hardness
+fn default_hardness() -> f32 {
+    1.0
+}
+
+// fluid kind and level of block
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Fluid {
+    // source block of fluid
+    pub source: Block,
+
+    // level from 1 to FLUID_LEVELS
+    pub level: u8,
+}
+
+impl Fluid {
+    // check if source block
+    pub fn is_source(&self) -> bool {
+        self.level == FLUID_LEVELS
+    }
+}
+
+// block registry
+#[derive(Clone, Debug)]
+pub struct BlockRegistry {
+    // definitions by id
+    defs: Vec<BlockDef>,
+
+    // ids by name
+    names: HashMap<String, Block>,
+
+    // fluid levels by id
+    fluids: HashMap<Block, Fluid>,
+}
+
+impl BlockRegistry {
+    // create registry with air
+    pub fn new() -> Self {
+        let mut registry = Self {
+            defs:   Vec::new(),
+            names:  HashMap::new(),
+            fluids: HashMap::new(),
+        };
+
+        registry.defs.push(BlockDef {
+            solid:       false,
+            transparent: true,
+            hardness:    0.0,
+            ..BlockDef::new("air", Textures::default())
+        });
+
+        registry.names.insert("air".to_string(), Block::AIR);
+        registry
+    }
+
+    // create registry with builtin blocks
+    pub fn builtin() -> Self {
+        Self::from_json(BUILTIN).expect("invalid builtin blocks")
+    }
+
+    // create registry from json definitions
+    pub fn from_json(text: &str) -> Result<Self> {
+        let defs = serde_json::from_str::<Vec<BlockDef>>(text)
+            .context("invalid block definitions")?;
+
+        let mut registry = Self::new();
+
+        for def in defs {
+            registry.register(def)?;
+        }
+
+        Ok(registry)
+    }
+
+    // load registry from json file
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: &std::path::Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+
+        Self::from_json(&text)
+    }
+
+    // register block type, with flowing levels after fluid sources
+    pub fn register(&mut self, def: BlockDef) -> Result<Block> {
+        let source = self.register_def(def.clone())?;
+
+        if def.fluid {
+            self.fluids.insert(source, Fluid { source, level: FLUID_LEVELS });
+
+            for level in (1..FLUID_LEVELS).rev() {
+                let block = self.register_def(BlockDef {
+                    name: format!("{}_{}", def.name, level),
+                    ..def.clone()
+                })?;
+
+                self.fluids.insert(block, Fluid { source, level });
+            }
+        }
+
+        Ok(source)
+    }
+
+    // register single definition
+    fn register_def(&mut self, def: BlockDef) -> Result<Block> {
+        ensure!(!self.names.contains_key(&def.name), "duplicate block: {}", def.name);
+        ensure!(def.emissive <= 15, "emissive level above 15: {}", def.name);
+
+        for face in 0..6 {
+            let Some([col, row]) = def.textures.face(face) else {
+                bail!("missing texture for face {}: {}", face, def.name);
+            };
+
+            ensure!(col < ATLAS_COLS && row < ATLAS_ROWS, "texture outside atlas: {}", def.name);
+        }
+
+        let Ok(id) = u16::try_from(self.defs.len()) else {
+            bail!("too many blocks");
+        };
+
+        let block = Block(id);
+
+        self.names.insert(def.name.clone(), block);
+        self.defs.push(def);
+
+        Ok(block)
+    }
+
+    // get definition
+    pub fn get(&self, block: Block) -> &BlockDef {
+        &self.defs[block.0 as usize]
+    }
+
+    // fluid kind and level of block
+    pub fn fluid(&self, block: Block) -> Option<Fluid> {
+        self.fluids.get(&block).copied()
+    }
+
+    // block of fluid at level
+    pub fn fluid_block(&self, source: Block, level: u8) -> Block {
+        let level = level.clamp(1, FLUID_LEVELS);
+
+        // levels follow their source in descending order
+        Block(source.0 + (FLUID_LEVELS - level) as u16)
+    }
+
+    // find block by name
+    pub fn id(&self, name: &str) -> Option<Block> {
+        self.names.get(name).copied()
+    }
+
+    // find block by name or fail
+    pub fn require(&self, name: &str) -> Result<Block> {
+        self.id(name).with_context(|| format!("missing block: {}", name))
+    }
+
+    // iterate definitions
+    pub fn iter(&self) -> impl Iterator<Item = (Block, &BlockDef)> {
+        self.defs
+            .iter()
+            .enumerate()
+            .map(|(id, def)| (Block(id as u16), def))
+    }
+}
+
+impl Default for BlockRegistry {
+    // default registry
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
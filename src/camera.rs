@@ -19,21 +19,24 @@ impl Camera {
         Self { pos, yaw, pitch }
     }
 
-    // calculate matrix
-    pub fn calc_matrix(&self) -> cgmath::Matrix4<f32> {
+    // view direction
+    pub fn direction(&self) -> Vector3<f32> {
         let (sin_pitch, cos_pitch) = self.pitch.0.sin_cos();
         let (sin_yaw, cos_yaw)     = self.yaw.0.sin_cos();
 
+        Vector3::new(
+            cos_pitch * cos_yaw,
+            sin_pitch,
+            cos_pitch * sin_yaw
+        ).normalize()
+    }
+
+    // calculate matrix
+    pub fn calc_matrix(&self) -> cgmath::Matrix4<f32> {
         // construct matrix
         Matrix4::look_to_rh(
             self.pos,
-
-            Vector3::new(
-                cos_pitch * cos_yaw,
-                sin_pitch,
-                cos_pitch * sin_yaw
-            ).normalize(),
-
+            self.direction(),
             Vector3::unit_y(),
         )
     }
@@ -31,6 +31,9 @@ pub struct CameraController {
 
     // w sprinting
     last_w: instant::Instant,
+
+    // free flying
+    spectator: bool,
 }
 
 impl CameraController {
@@ -54,9 +57,16 @@ impl CameraController {
             down: 0.0,
 
             last_w: instant::Instant::now(),
+
+            spectator: false,
         }
     }
 
+    // check if free flying
+    pub fn spectator(&self) -> bool {
+        self.spectator
+    }
+
     // handle window event
     pub fn event(&mut self, event: &WindowEvent) -> bool {
         if let WindowEvent::KeyboardInput {
@@ -116,6 +126,16 @@ impl CameraController {
                     true
                 }
 
+                KeyCode::KeyF => {
+                    if state.is_pressed() {
+                        // switch flying
+                        self.spectator = !self.spectator;
+                        log::info!("spectator: {}", self.spectator);
+                    }
+
+                    true
+                }
+
                 _ => false,
             }
         } else {
@@ -129,22 +149,37 @@ impl CameraController {
         self.rot_v = dy as f32;
     }
 
-    // update camera
-    pub fn update_camera(&mut self, camera: &mut Camera, dt: instant::Duration) {
-        let dt = dt.as_secs_f32();
+    // horizontal walking direction
+    pub fn direction(&self, camera: &Camera) -> Vector3<f32> {
         let (yaw_sin, yaw_cos) = camera.yaw.0.sin_cos();
 
         let forward = Vector3::new(yaw_cos, 0.0, yaw_sin).normalize();
         let right   = Vector3::new(-yaw_sin, 0.0, yaw_cos).normalize();
 
-        // z movement
-        camera.pos += forward * (self.forward - self.backward) * self.speed * dt;
+        forward * (self.forward - self.backward) + right * (self.right - self.left)
+    }
+
+    // check if jump is held
+    pub fn jumping(&self) -> bool {
+        self.up > 0.0
+    }
 
-        // x movement
-        camera.pos += right * (self.right - self.left) * self.speed * dt;
+    // check if sprinting
+    pub fn sprinting(&self) -> bool {
+        self.speed > self.initial
+    }
 
-        // y movement
-        camera.pos.y += (self.up - self.down) * self.speed * dt;
+    // update camera
+    pub fn update_camera(&mut self, camera: &mut Camera, dt: instant::Duration) {
+        let dt = dt.as_secs_f32();
+
+        if self.spectator {
+            // xz movement
+            camera.pos += self.direction(camera) * self.speed * dt;
+
+            // y movement
+            camera.pos.y += (self.up - self.down) * self.speed * dt;
+        }
 
         // view direction
         camera.yaw   += Rad(self.rot_h) * self.sens * dt;
@@ -1,131 +1,355 @@
+use crate::block::{Block, BlockRegistry};
+use crate::chunk_blocks::ChunkBlocks;
+use crate::generator::Generator;
 use crate::instance::Instance;
-use crate::vertex::TEX_H;
+use crate::light::{Channel, ChunkLight, MAX_LIGHT};
+use crate::mesh::{self, AXES, CORNERS};
 
-use noise::permutationtable::PermutationTable;
-use noise::core::open_simplex::open_simplex_2d;
+use std::mem;
 
-use std::cmp::Ordering;
-
-// chunk size
+// chunk size along every axis
 pub const CHUNK_SIZE: u8 = 16;
 
-// block type
-enum Block {
-    Air,
-    Dirt,
-    Grass,
+// blocks per chunk
+const CHUNK_VOLUME: usize = CHUNK_SIZE as usize * CHUNK_SIZE as usize * CHUNK_SIZE as usize;
+
+// chunk position in chunk units
+pub type ChunkPos = (i32, i32, i32);
+
+// offsets of face neighbours
+pub const NEIGHBOURS: [[i32; 3]; 6] = [[0, 0, 1], [0, 0, -1], [-1, 0, 0], [1, 0, 0], [0, 1, 0], [0, -1, 0]];
+
+// offsets of horizontal neighbours
+pub const SIDES: [[i32; 3]; 4] = [[0, 0, 1], [0, 0, -1], [-1, 0, 0], [1, 0, 0]];
+
+// offset position
+pub fn offset(pos: [i32; 3], by: [i32; 3]) -> [i32; 3] {
+    [pos[0] + by[0], pos[1] + by[1], pos[2] + by[2]]
 }
 
-impl Block {
-    // check if air
-    fn is_air(&self) -> bool {
-        matches!(self, Self::Air)
-    }
+// neighbouring chunks
+#[derive(Copy, Clone, Default)]
+pub struct Neighbours<'a> {
+    // front and back
+    pub front: Option<&'a Chunk>,
+    pub back:  Option<&'a Chunk>,
 
-    // texture mapping
-    fn texture(&self) -> f32 {
-        let pos = match self {
-            Self::Air   => unreachable!(),
-            Self::Dirt  => 2.0,
-            Self::Grass => 3.0,
-        };
+    // left and right
+    pub left:  Option<&'a Chunk>,
+    pub right: Option<&'a Chunk>,
 
-        pos * TEX_H
-    }
+    // top and bottom
+    pub top:    Option<&'a Chunk>,
+    pub bottom: Option<&'a Chunk>,
 }
 
-// chunk
+// cubic section of world
 pub struct Chunk {
     // position
     pos_x: i32,
+    pos_y: i32,
     pos_z: i32,
 
     // blocks
-    blocks: Vec<Vec<Vec<Block>>>,
+    blocks: ChunkBlocks,
+
+    // sky and block light
+    light: ChunkLight,
+
+    // neighbours whose structures were placed, one bit per chunk offset
+    decorated: u32,
+
+    // changed since generated or saved
+    modified: bool,
 }
 
 impl Chunk {
     // create chunk
-    pub fn new(pos_x: i32, pos_z: i32, hashers: &[(u8, PermutationTable)]) -> Self {
-        let off_x = pos_x * CHUNK_SIZE as i32;
-        let off_z = pos_z * CHUNK_SIZE as i32;
-
-        // get hashers sum
-        let divisor =
-            hashers
-                .iter()
-                .map(|(n, _)| 1 / n)
-                .sum::<u8>() as f64;
-
-        let blocks = (0..CHUNK_SIZE).map(|z| {
-            (0..CHUNK_SIZE).map(move |x| {
-                // sample noise for height
-                let raw_height =
-                    hashers
-                        .iter()
-                        .map(|(n, hasher)| {
-                            open_simplex_2d([
-                                ((x as f32 + off_x as f32) * *n as f32 * 0.025) as f64,
-                                ((z as f32 + off_z as f32) * *n as f32 * 0.025) as f64,
-                            ], hasher) / *n as f64
-                        }).sum::<f64>() / divisor;
-
-                let height = ((raw_height + 1.0) * 15.0).powf(0.9) as i32;
-
-                (0..64).map(move |y| {
-                    // show dirt if below height
-                    match height.cmp(&y) {
-                        Ordering::Equal   => Block::Grass,
-                        Ordering::Greater => Block::Dirt,
-                        _ => Block::Air
-                    }
-                }).collect::<Vec<_>>()
-            }).collect::<Vec<_>>()
-        }).collect::<Vec<_>>();
-
-        Self { pos_x, pos_z, blocks }
+    pub fn new(pos_x: i32, pos_y: i32, pos_z: i32, generator: &Generator) -> Self {
+        Self::generated(pos_x, pos_y, pos_z, generator).0
+    }
+
+    // create chunk with highest blocks its columns may hold
+    pub fn generated(pos_x: i32, pos_y: i32, pos_z: i32, generator: &Generator) -> (Self, Vec<i32>) {
+        let mut chunk = Self::empty(pos_x, pos_y, pos_z);
+        let (blocks, tops) = generator.generate_with_tops((pos_x, pos_y, pos_z));
+
+        // keep sections above terrain empty
+        if let Some(blocks) = blocks {
+            chunk.blocks = blocks;
+        }
+
+        (chunk, tops)
+    }
+
+    // create empty chunk
+    pub fn empty(pos_x: i32, pos_y: i32, pos_z: i32) -> Self {
+        Self {
+            pos_x,
+            pos_y,
+            pos_z,
+
+            blocks:    ChunkBlocks::uniform(CHUNK_VOLUME, Block::AIR),
+            light:     ChunkLight::default(),
+            decorated: 0,
+            modified:  false,
+        }
+    }
+
+    // chunk position
+    pub fn pos(&self) -> ChunkPos {
+        (self.pos_x, self.pos_y, self.pos_z)
+    }
+
+    // world position of first block corner
+    pub fn origin(&self) -> [f32; 3] {
+        let size = CHUNK_SIZE as i32;
+
+        [
+            (self.pos_x * size) as f32,
+            (self.pos_y * size) as f32,
+            (self.pos_z * size) as f32,
+        ]
+    }
+
+    // check if chunk holds only air
+    pub fn is_empty(&self) -> bool {
+        self.blocks.single() == Some(Block::AIR)
+    }
+
+    // block storage
+    pub fn blocks(&self) -> &ChunkBlocks {
+        &self.blocks
+    }
+
+    // estimated memory in bytes
+    pub fn memory(&self) -> usize {
+        mem::size_of::<Self>() + self.blocks.memory() + self.light.memory()
+    }
+
+    // index of local position
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        let size = CHUNK_SIZE as usize;
+        (z * size + x) * size + y
+    }
+
+    // get block at local position
+    pub fn block(&self, x: usize, y: usize, z: usize) -> Block {
+        self.blocks.get(Self::index(x, y, z))
+    }
+
+    // set block at local position
+    pub fn set(&mut self, x: usize, y: usize, z: usize, block: Block) {
+        self.blocks.set(Self::index(x, y, z), block);
+        self.modified = true;
+    }
+
+    // set generated block at local position, keeping chunk unchanged
+    pub fn place(&mut self, x: usize, y: usize, z: usize, block: Block) {
+        self.blocks.set(Self::index(x, y, z), block);
+    }
+
+    // replace blocks of whole chunk
+    pub fn set_blocks(&mut self, blocks: ChunkBlocks) {
+        self.blocks = blocks;
+    }
+
+    // sky and block light at local position
+    pub fn light(&self, x: usize, y: usize, z: usize) -> [u8; 2] {
+        self.light.levels(Self::index(x, y, z))
+    }
+
+    // set light of channel at local position, returning bytes allocated
+    pub fn set_light(&mut self, x: usize, y: usize, z: usize, channel: Channel, level: u8) -> usize {
+        self.light.set(Self::index(x, y, z), channel, level)
+    }
+
+    // light storage
+    pub fn lighting(&self) -> &ChunkLight {
+        &self.light
+    }
+
+    // replace light of whole chunk
+    pub fn set_lighting(&mut self, light: ChunkLight) {
+        self.light = light;
+    }
+
+    // bit of neighbour at chunk offset
+    fn decoration_bit((dx, dy, dz): ChunkPos) -> u32 {
+        1 << ((dx + 1) * 9 + (dy + 1) * 3 + dz + 1)
+    }
+
+    // check if structures of neighbour at chunk offset were placed
+    pub fn decorated(&self, offset: ChunkPos) -> bool {
+        self.decorated & Self::decoration_bit(offset) != 0
+    }
+
+    // record structures of neighbour at chunk offset as placed
+    pub fn set_decorated(&mut self, offset: ChunkPos) {
+        self.decorated |= Self::decoration_bit(offset);
+    }
+
+    // neighbours whose structures were placed
+    pub fn decorations(&self) -> u32 {
+        self.decorated
+    }
+
+    // replace neighbours whose structures were placed
+    pub fn set_decorations(&mut self, decorated: u32) {
+        self.decorated = decorated;
+    }
+
+    // check if chunk needs saving
+    pub fn modified(&self) -> bool {
+        self.modified
+    }
+
+    // mark chunk as saved, packing blocks its edits settled on
+    pub fn mark_saved(&mut self) {
+        self.blocks.compact();
+        self.modified = false;
+    }
+
+    // chunk and local position holding position, none if neighbour is not known
+    fn resolve<'a>(&'a self, x: i32, y: i32, z: i32, neighbours: &Neighbours<'a>) -> Option<(&'a Chunk, [usize; 3])> {
+        let size = CHUNK_SIZE as i32;
+
+        // only chunks sharing a face are known, edges and corners are not
+        if [x, y, z].iter().filter(|coord| !(0..size).contains(*coord)).count() > 1 {
+            return None;
+        }
+
+        let neighbour = match (x, y, z) {
+            (x, _, _) if x < 0     => neighbours.left,
+            (x, _, _) if x >= size => neighbours.right,
+            (_, y, _) if y < 0     => neighbours.bottom,
+            (_, y, _) if y >= size => neighbours.top,
+            (_, _, z) if z < 0     => neighbours.back,
+            (_, _, z) if z >= size => neighbours.front,
+
+            // inside chunk
+            _ => Some(self),
+        };
+
+        neighbour.map(|chunk| (chunk, [x, y, z].map(|coord| coord.rem_euclid(size) as usize)))
+    }
+
+    // get block at local position, including neighbours
+    pub fn block_at(&self, x: i32, y: i32, z: i32, neighbours: &Neighbours) -> Block {
+        // render border faces until neighbour loads
+        self.resolve(x, y, z, neighbours).map_or(Block::AIR, |(chunk, [x, y, z])| chunk.block(x, y, z))
+    }
+
+    // packed sky and block light at local position, including neighbours
+    pub fn light_at(&self, x: i32, y: i32, z: i32, neighbours: &Neighbours) -> u8 {
+        // light border faces fully until neighbour loads
+        let [sky, block] =
+            self.resolve(x, y, z, neighbours).map_or([MAX_LIGHT, 0], |(chunk, [x, y, z])| chunk.light(x, y, z));
+
+        sky << 4 | block
+    }
+
+    // check if face towards position is visible
+    pub fn face_visible(
+        &self,
+        block:      Block,
+        pos:        [i32; 3],
+        neighbours: &Neighbours,
+        registry:   &BlockRegistry,
+    ) -> bool {
+        let next = self.block_at(pos[0], pos[1], pos[2], neighbours);
+
+        // hide faces between same transparent blocks
+        next != block && registry.get(next).transparent
+    }
+
+    // brightness of face corners at local position, from 0 to 3
+    pub fn face_ao(&self, pos: [i32; 3], face: usize, neighbours: &Neighbours, registry: &BlockRegistry) -> [u8; 4] {
+        let (axis, dir, u_axis, v_axis) = AXES[face];
+
+        // blocks around the one in front of face
+        let mut front = pos;
+        front[axis] += dir;
+
+        let solid = |du: i32, dv: i32| {
+            let mut next = front;
+            next[u_axis] += du;
+            next[v_axis] += dv;
+
+            !registry.get(self.block_at(next[0], next[1], next[2], neighbours)).transparent
+        };
+
+        CORNERS[face].map(|(corner, _)| {
+            let du = if corner[u_axis] == 0 { -1 } else { 1 };
+            let dv = if corner[v_axis] == 0 { -1 } else { 1 };
+
+            mesh::occlusion(solid(du, 0), solid(0, dv), solid(du, dv))
+        })
     }
 
     // get instances
-    pub fn instances(&self) -> Vec<Instance> {
-        // simplify blocks
-        let blocks = self.blocks.iter().enumerate().flat_map(|(z, row)| {
-            row.iter().enumerate().flat_map(move |(x, col)| {
-                col.iter().enumerate().map(move |(y, block)| {
-                    ((x, y, z), block)
-                })
-            })
-        });
-
-        // get instances
+    pub fn instances(&self, neighbours: &Neighbours, registry: &BlockRegistry) -> Vec<Instance> {
+        // nothing to draw
+        if self.is_empty() {
+            return Vec::new();
+        }
+
+        let origin = self.origin();
+        let size   = CHUNK_SIZE as usize;
+
+        // blocks in storage order
+        let blocks =
+            itertools::iproduct!(0..size, 0..size, 0..size)
+                .zip(self.blocks.iter())
+                .map(|((z, x, y), block)| ((x, y, z), block));
+
+        // get instances, blended blocks are meshed separately
         blocks
-            .filter(|(_, block)| !block.is_air())
+            .filter(|(_, block)| !block.is_air() && !registry.get(*block).blended())
             .map(|((x, y, z), block)| {
+                let (x, y, z) = (x as i32, y as i32, z as i32);
+
+                // blocks in front of faces
+                let next = [
+                    // front and back
+                    [x, y, z + 1],
+                    [x, y, z - 1],
+
+                    // left and right
+                    [x - 1, y, z],
+                    [x + 1, y, z],
+
+                    // top and bottom
+                    [x, y + 1, z],
+                    [x, y - 1, z],
+                ];
+
+                let faces = next.map(|pos| self.face_visible(block, pos, neighbours, registry));
+
                 Instance::new(
                     // position
                     [
-                        x as f32 + (self.pos_x * CHUNK_SIZE as i32) as f32,
-                        y as f32 - 7.5,
-                        z as f32 + (self.pos_z * CHUNK_SIZE as i32) as f32,
+                        origin[0] + x as f32 + 0.5,
+                        origin[1] + y as f32 + 0.5,
+                        origin[2] + z as f32 + 0.5,
                     ],
 
-                    // get texture
-                    block.texture(),
+                    // get face tiles
+                    registry.get(block).tiles(),
 
                     // render faces
-                    [
-                        // front and back
-                        z + 1 == self.blocks.len() || self.blocks[z + 1][x][y].is_air(),
-                        z == 0 || self.blocks[z - 1][x][y].is_air(),
-
-                        // left and right
-                        x == 0 || self.blocks[z][x - 1][y].is_air(),
-                        x + 1 == self.blocks[z].len() || self.blocks[z][x + 1][y].is_air(),
-
-                        // top and bottom
-                        y + 1 == self.blocks[z][x].len() || self.blocks[z][x][y + 1].is_air(),
-                        y == 0 || self.blocks[z][x][y - 1].is_air(),
-                    ]
+                    faces,
+
+                    // light of rendered faces
+                    [0, 1, 2, 3, 4, 5].map(|face| {
+                        let [x, y, z] = next[face];
+                        if faces[face] { self.light_at(x, y, z, neighbours) } else { 0 }
+                    }),
+
+                    // corner brightness of rendered faces
+                    [0, 1, 2, 3, 4, 5].map(|face| {
+                        if faces[face] { self.face_ao([x, y, z], face, neighbours, registry) } else { [0; 4] }
+                    }),
                 )
             }).collect()
     }
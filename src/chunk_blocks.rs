@@ -0,0 +1,215 @@
+use crate::block::Block;
+
+use itertools::Either;
+use std::{iter, mem};
+
+// bits per word of packed indices
+const WORD_BITS: usize = u64::BITS as usize;
+
+// blocks as palette and bit-packed indices
+#[derive(Clone, Debug)]
+pub struct ChunkBlocks {
+    // number of blocks
+    len: usize,
+
+    // distinct blocks
+    palette: Vec<Block>,
+
+    // bits per index, zero while uniform
+    bits: u8,
+
+    // packed palette indices
+    words: Vec<u64>,
+}
+
+impl ChunkBlocks {
+    // create blocks of single type
+    pub fn uniform(len: usize, block: Block) -> Self {
+        Self {
+            len,
+            palette: vec![block],
+            bits:    0,
+            words:   Vec::new(),
+        }
+    }
+
+    // number of blocks
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // check if there are no blocks
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // single block type, if uniform
+    pub fn single(&self) -> Option<Block> {
+        (self.bits == 0).then_some(self.palette[0])
+    }
+
+    // distinct blocks, possibly including unused ones
+    pub fn palette(&self) -> &[Block] {
+        &self.palette
+    }
+
+    // bits per index
+    pub fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    // estimated heap memory in bytes
+    pub fn memory(&self) -> usize {
+        self.palette.capacity() * mem::size_of::<Block>() + self.words.capacity() * mem::size_of::<u64>()
+    }
+
+    // get block at index
+    #[inline]
+    pub fn get(&self, index: usize) -> Block {
+        debug_assert!(index < self.len);
+
+        if self.bits == 0 {
+            return self.palette[0];
+        }
+
+        self.palette[self.index(index)]
+    }
+
+    // set block at index
+    #[inline]
+    pub fn set(&mut self, index: usize, block: Block) {
+        debug_assert!(index < self.len);
+
+        let entry = match self.palette.iter().position(|&known| known == block) {
+            Some(entry) => entry,
+
+            // new palette entry
+            None => {
+                self.palette.push(block);
+
+                if self.palette.len() > 1 << self.bits {
+                    self.repack(Self::bits_for(self.palette.len()));
+                }
+
+                self.palette.len() - 1
+            }
+        };
+
+        if self.bits > 0 {
+            self.set_index(index, entry);
+        }
+    }
+
+    // iterate blocks in index order
+    pub fn iter(&self) -> impl Iterator<Item = Block> + '_ {
+        if self.bits == 0 {
+            return Either::Left(iter::repeat_n(self.palette[0], self.len));
+        }
+
+        let bits = self.bits as usize;
+        let mask = (1u64 << bits) - 1;
+
+        // unpack whole words at once
+        let blocks = self.words.iter().flat_map(move |&word| {
+            (0..WORD_BITS / bits).map(move |entry| self.palette[((word >> (entry * bits)) & mask) as usize])
+        });
+
+        Either::Right(blocks.take(self.len))
+    }
+
+    // drop unused palette entries and shrink indices
+    pub fn compact(&mut self) {
+        if self.bits == 0 {
+            return;
+        }
+
+        let packed = self.iter().collect();
+        *self = packed;
+    }
+
+    // smallest power of two bits holding palette
+    fn bits_for(entries: usize) -> u8 {
+        let needed = usize::BITS - (entries - 1).leading_zeros();
+        needed.max(1).next_power_of_two() as u8
+    }
+
+    // change bits per index, keeping blocks
+    fn repack(&mut self, bits: u8) {
+        let palette = mem::take(&mut self.palette);
+        let words   = vec![0; self.len.div_ceil(WORD_BITS / bits as usize)];
+
+        let old = mem::replace(self, Self { len: self.len, palette, bits, words });
+
+        if old.bits > 0 {
+            for index in 0..self.len {
+                self.set_index(index, old.index(index));
+            }
+        }
+    }
+
+    // word and shift of index
+    #[inline]
+    fn locate(&self, index: usize) -> (usize, usize) {
+        // entries per word is a power of two
+        let per_word = (WORD_BITS / self.bits as usize).trailing_zeros();
+        (index >> per_word, (index & ((1 << per_word) - 1)) * self.bits as usize)
+    }
+
+    // palette entry at index
+    #[inline]
+    fn index(&self, index: usize) -> usize {
+        let (word, shift) = self.locate(index);
+        let mask          = (1u64 << self.bits) - 1;
+
+        ((self.words[word] >> shift) & mask) as usize
+    }
+
+    // write palette entry at index
+    #[inline]
+    fn set_index(&mut self, index: usize, entry: usize) {
+        let (word, shift) = self.locate(index);
+        let mask          = ((1u64 << self.bits) - 1) << shift;
+
+        self.words[word] = (self.words[word] & !mask) | ((entry as u64) << shift & mask);
+    }
+}
+
+impl FromIterator<Block> for ChunkBlocks {
+    // pack blocks, building palette once
+    fn from_iter<I: IntoIterator<Item = Block>>(blocks: I) -> Self {
+        let mut palette = Vec::new();
+
+        let indices =
+            blocks
+                .into_iter()
+                .map(|block| {
+                    match palette.iter().position(|&known| known == block) {
+                        Some(entry) => entry,
+                        None => {
+                            palette.push(block);
+                            palette.len() - 1
+                        }
+                    }
+                }).collect::<Vec<_>>();
+
+        // uniform fast path
+        if palette.len() <= 1 {
+            return Self::uniform(indices.len(), palette.first().copied().unwrap_or_default());
+        }
+
+        let mut packed = Self {
+            len:   indices.len(),
+            bits:  0,
+            words: Vec::new(),
+            palette,
+        };
+
+        packed.repack(Self::bits_for(packed.palette.len()));
+
+        for (index, entry) in indices.into_iter().enumerate() {
+            packed.set_index(index, entry);
+        }
+
+        packed
+    }
+}
@@ -0,0 +1,150 @@
+use crate::mesh::{SolidMesh, Mesh};
+use wgpu::util::DeviceExt;
+
+// blocks of eye movement per block of distance before sorting again
+const RESORT_FALLOFF: f32 = 1.0 / 16.0;
+
+// chunk gpu buffers
+pub enum ChunkBuffers {
+    // block instances
+    Instanced {
+        buf:   wgpu::Buffer,
+        count: u32,
+    },
+
+    // merged mesh
+    Mesh {
+        vtx_buf: wgpu::Buffer,
+        idx_buf: wgpu::Buffer,
+        count:   u32,
+    },
+}
+
+impl ChunkBuffers {
+    // upload opaque chunk geometry
+    pub fn new(mesh: &SolidMesh, device: &wgpu::Device) -> Option<Self> {
+        match mesh {
+            SolidMesh::Instanced(instances) => {
+                if instances.is_empty() {
+                    return None;
+                }
+
+                // create instance buffer
+                let buf = device.create_buffer_init(
+                    &wgpu::util::BufferInitDescriptor {
+                        label:    Some("instance_buf"),
+                        contents: bytemuck::cast_slice(instances),
+                        usage:    wgpu::BufferUsages::VERTEX,
+                    }
+                );
+
+                Some(Self::Instanced { buf, count: instances.len() as u32 })
+            }
+
+            SolidMesh::Greedy(mesh) => Self::mesh(mesh, device),
+        }
+    }
+
+    // upload merged mesh
+    pub fn mesh(mesh: &Mesh, device: &wgpu::Device) -> Option<Self> {
+        if mesh.indices.is_empty() {
+            return None;
+        }
+
+        // create mesh buffers
+        let vtx_buf = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label:    Some("mesh_vtx_buf"),
+                contents: bytemuck::cast_slice(&mesh.vertices),
+                usage:    wgpu::BufferUsages::VERTEX,
+            }
+        );
+
+        let idx_buf = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label:    Some("mesh_idx_buf"),
+                contents: bytemuck::cast_slice(&mesh.indices),
+                usage:    wgpu::BufferUsages::INDEX,
+            }
+        );
+
+        Some(Self::Mesh { vtx_buf, idx_buf, count: mesh.indices.len() as u32 })
+    }
+}
+
+// blended chunk geometry, sorted back to front as the eye moves
+pub struct TranslucentBuffers {
+    // mesh kept for sorting
+    mesh: Mesh,
+
+    // chunk center
+    center: [f32; 3],
+
+    // eye of last sort
+    sorted_from: [f32; 3],
+
+    // gpu buffers
+    pub vtx_buf: wgpu::Buffer,
+    pub idx_buf: wgpu::Buffer,
+    pub count:   u32,
+}
+
+impl TranslucentBuffers {
+    // upload mesh sorted for eye
+    pub fn new(mut mesh: Mesh, center: [f32; 3], eye: [f32; 3], device: &wgpu::Device) -> Option<Self> {
+        if mesh.indices.is_empty() {
+            return None;
+        }
+
+        mesh.sort_back_to_front(eye);
+
+        // create mesh buffers
+        let vtx_buf = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label:    Some("translucent_vtx_buf"),
+                contents: bytemuck::cast_slice(&mesh.vertices),
+                usage:    wgpu::BufferUsages::VERTEX,
+            }
+        );
+
+        // indices are rewritten when sorting
+        let idx_buf = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label:    Some("translucent_idx_buf"),
+                contents: bytemuck::cast_slice(&mesh.indices),
+                usage:    wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+
+        Some(Self {
+            count: mesh.indices.len() as u32,
+            sorted_from: eye,
+            mesh,
+            center,
+            vtx_buf,
+            idx_buf,
+        })
+    }
+
+    // distance from eye to chunk center
+    pub fn distance(&self, eye: [f32; 3]) -> f32 {
+        distance(self.center, eye)
+    }
+
+    // sort again once eye moved far enough for distant chunks to change order
+    pub fn sort(&mut self, eye: [f32; 3], queue: &wgpu::Queue) {
+        if distance(self.sorted_from, eye) < 1.0 + self.distance(eye) * RESORT_FALLOFF {
+            return;
+        }
+
+        self.mesh.sort_back_to_front(eye);
+        self.sorted_from = eye;
+
+        queue.write_buffer(&self.idx_buf, 0, bytemuck::cast_slice(&self.mesh.indices));
+    }
+}
+
+// distance between points
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (0..3).map(|axis| (a[axis] - b[axis]).powi(2)).sum::<f32>().sqrt()
+}
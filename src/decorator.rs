@@ -0,0 +1,341 @@
+use crate::block::{Block, BlockRegistry};
+use crate::chunk::{Chunk, ChunkPos, CHUNK_SIZE};
+use crate::generator::Generator;
+use crate::seed::WorldSeed;
+
+use anyhow::Result;
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use std::ops::RangeInclusive;
+
+// blocks along chunk edge
+const SIZE: i32 = CHUNK_SIZE as i32;
+
+// tree and boulder attempts per chunk
+const TREE_ATTEMPTS:    f64 = 3.0;
+const BOULDER_ATTEMPTS: f64 = 1.0;
+
+// trunk heights
+const TRUNK: RangeInclusive<i32> = 4..=6;
+
+// block placed by decoration
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Placement {
+    // world position
+    pub pos: [i32; 3],
+
+    // placed block
+    pub block: Block,
+
+    // only block it may replace
+    pub replaces: Block,
+}
+
+// decoration amounts, scaling attempts per chunk
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Decorations {
+    // trees on grass and snow
+    pub trees: f64,
+
+    // ore veins in stone
+    pub ores: f64,
+
+    // stone boulders on the surface
+    pub boulders: f64,
+}
+
+impl Decorations {
+    // bare terrain
+    pub const NONE: Decorations = Decorations { trees: 0.0, ores: 0.0, boulders: 0.0 };
+}
+
+impl Default for Decorations {
+    // default amounts
+    fn default() -> Self {
+        Self {
+            trees:    1.0,
+            ores:     1.0,
+            boulders: 1.0,
+        }
+    }
+}
+
+// ore vein kind
+struct Ore {
+    // ore block
+    block: Block,
+
+    // veins per chunk
+    veins: f64,
+
+    // blocks per vein
+    size: RangeInclusive<u32>,
+
+    // highest vein start
+    max_y: i32,
+}
+
+// structure placement after terrain generation
+pub struct Decorator {
+    // world seed
+    seed: WorldSeed,
+
+    // decoration amounts
+    decorations: Decorations,
+
+    // structure blocks
+    log:    Block,
+    leaves: Block,
+    stone:  Block,
+
+    // blocks trees grow on
+    soil: [Block; 2],
+
+    // sea fill boulders must not rest on
+    water: Block,
+
+    // ore kinds
+    ores: Vec<Ore>,
+}
+
+impl Decorator {
+    // create decorator
+    pub fn new(seed: WorldSeed, registry: &BlockRegistry, decorations: Decorations) -> Result<Self> {
+        Ok(Self {
+            seed,
+            decorations,
+
+            log:    registry.require("log")?,
+            leaves: registry.require("leaves")?,
+            stone:  registry.require("stone")?,
+
+            soil:  [registry.require("grass")?, registry.require("snow")?],
+            water: registry.require("water")?,
+
+            ores: vec![
+                Ore { block: registry.require("coal_ore")?, veins: 8.0, size: 6..=12, max_y: 128 },
+                Ore { block: registry.require("iron_ore")?, veins: 5.0, size: 4..=8,  max_y: 32 },
+                Ore { block: registry.require("gold_ore")?, veins: 1.5, size: 3..=6,  max_y: -16 },
+            ],
+        })
+    }
+
+    // decoration amounts
+    pub fn decorations(&self) -> Decorations {
+        self.decorations
+    }
+
+    // place structures starting in chunk, returning blocks outside it
+    pub fn decorate(&self, chunk: &mut Chunk, generator: &Generator) -> Vec<Placement> {
+        if chunk.is_empty() {
+            return Vec::new();
+        }
+
+        let mut placements =
+            self.structures(chunk.pos(), generator, |x, y, z| chunk.block(x as usize, y as usize, z as usize));
+
+        let [off_x, off_y, off_z] = chunk.origin().map(|off| off as i32);
+
+        // apply own blocks in order, keep the rest for neighbours
+        placements.retain(|placement| {
+            let [x, y, z] = placement.pos;
+            let local     = [x - off_x, y - off_y, z - off_z];
+
+            if local.iter().any(|coord| !(0..SIZE).contains(coord)) {
+                return true;
+            }
+
+            let [x, y, z] = local.map(|coord| coord as usize);
+
+            if chunk.block(x, y, z) == placement.replaces {
+                chunk.place(x, y, z, placement.block);
+            }
+
+            false
+        });
+
+        placements
+    }
+
+    // blocks structures of chunk place outside it, sampling only the terrain they rest on
+    pub fn placements(&self, pos: ChunkPos, generator: &Generator, tops: &[i32]) -> Vec<Placement> {
+        let terrain = generator.terrain(pos, tops);
+
+        if terrain.is_empty() {
+            return Vec::new();
+        }
+
+        let mut placements = self.structures(pos, generator, |x, y, z| terrain.block(x, y, z));
+
+        placements.retain(|placement| {
+            let [x, y, z] = placement.pos;
+            [x, y, z].map(|coord| coord.div_euclid(SIZE)) != [pos.0, pos.1, pos.2]
+        });
+
+        placements
+    }
+
+    // structure blocks of chunk in placement order, given its terrain by local position
+    fn structures(&self, pos: ChunkPos, generator: &Generator, block: impl Fn(i32, i32, i32) -> Block) -> Vec<Placement> {
+        let origin         = [pos.0 * SIZE, pos.1 * SIZE, pos.2 * SIZE];
+        let mut rng        = self.seed.rng_at("decoration", pos);
+        let mut placements = Vec::new();
+
+        self.ores(origin, &block, &mut rng, &mut placements);
+        self.boulders(origin, &block, generator, &mut rng, &mut placements);
+        self.trees(origin, &block, generator, &mut rng, &mut placements);
+
+        placements
+    }
+
+    // attempts for amount, rounding fractions randomly
+    fn attempts(rng: &mut StdRng, amount: f64) -> u32 {
+        let amount = amount.max(0.0);
+        amount as u32 + rng.gen_bool(amount.fract()) as u32
+    }
+
+    // random local position in chunk
+    fn local(rng: &mut StdRng) -> [i32; 3] {
+        [rng.gen_range(0..SIZE), rng.gen_range(0..SIZE), rng.gen_range(0..SIZE)]
+    }
+
+    // highest local block in column matching filter, with air above
+    fn ground(
+        [off_x, off_y, off_z]: [i32; 3],
+        block:     &impl Fn(i32, i32, i32) -> Block,
+        generator: &Generator,
+        x:         i32,
+        z:         i32,
+        filter:    impl Fn(Block) -> bool,
+    ) -> Option<i32> {
+        // top block looks into the chunk above
+        let air_above = |y: i32| {
+            if y + 1 < SIZE {
+                block(x, y + 1, z) == Block::AIR
+            } else {
+                generator.block_at(x + off_x, y + 1 + off_y, z + off_z) == Block::AIR
+            }
+        };
+
+        (0..SIZE)
+            .rev()
+            .find(|&y| filter(block(x, y, z)) && air_above(y))
+    }
+
+    // ore veins wandering through stone
+    fn ores(
+        &self,
+        origin:     [i32; 3],
+        block:      &impl Fn(i32, i32, i32) -> Block,
+        rng:        &mut StdRng,
+        placements: &mut Vec<Placement>,
+    ) {
+        for ore in &self.ores {
+            for _ in 0..Self::attempts(rng, ore.veins * self.decorations.ores) {
+                let start = Self::local(rng);
+                let size  = rng.gen_range(ore.size.clone());
+
+                let [x, y, z] = start;
+
+                // veins start in stone below their limit
+                if block(x, y, z) != self.stone || start[1] + origin[1] > ore.max_y {
+                    continue;
+                }
+
+                let mut pos = [0, 1, 2].map(|axis| start[axis] + origin[axis]);
+
+                for _ in 0..size {
+                    placements.push(Placement { pos, block: ore.block, replaces: self.stone });
+                    pos[rng.gen_range(0..3)] += if rng.gen_bool(0.5) { 1 } else { -1 };
+                }
+            }
+        }
+    }
+
+    // round stone boulders resting on the surface
+    fn boulders(
+        &self,
+        origin:     [i32; 3],
+        block:      &impl Fn(i32, i32, i32) -> Block,
+        generator:  &Generator,
+        rng:        &mut StdRng,
+        placements: &mut Vec<Placement>,
+    ) {
+        let [off_x, off_y, off_z] = origin;
+
+        for _ in 0..Self::attempts(rng, BOULDER_ATTEMPTS * self.decorations.boulders) {
+            let [x, _, z] = Self::local(rng);
+            let radius    = rng.gen_range(1..=2);
+            let chance    = rng.gen::<f64>();
+
+            if chance >= generator.biome(x + off_x, z + off_z).boulders() {
+                continue;
+            }
+
+            let Some(y) = Self::ground(origin, block, generator, x, z, |block| block != Block::AIR && block != self.water) else {
+                continue;
+            };
+
+            // overlap the ground by one block
+            let center = [x + off_x, y + off_y + radius - 1, z + off_z];
+            let reach  = (radius as f64 + 0.5).powi(2);
+
+            for (dx, dy, dz) in itertools::iproduct!(-radius..=radius, -radius..=radius, -radius..=radius) {
+                if (dx * dx + dy * dy + dz * dz) as f64 <= reach {
+                    let pos = [center[0] + dx, center[1] + dy, center[2] + dz];
+                    placements.push(Placement { pos, block: self.stone, replaces: Block::AIR });
+                }
+            }
+        }
+    }
+
+    // trees with trunks and leaf crowns
+    fn trees(
+        &self,
+        origin:     [i32; 3],
+        block:      &impl Fn(i32, i32, i32) -> Block,
+        generator:  &Generator,
+        rng:        &mut StdRng,
+        placements: &mut Vec<Placement>,
+    ) {
+        let [off_x, off_y, off_z] = origin;
+
+        for _ in 0..Self::attempts(rng, TREE_ATTEMPTS * self.decorations.trees) {
+            let [x, _, z] = Self::local(rng);
+            let height    = rng.gen_range(TRUNK);
+            let chance    = rng.gen::<f64>();
+
+            if chance >= generator.biome(x + off_x, z + off_z).trees() {
+                continue;
+            }
+
+            let Some(y) = Self::ground(origin, block, generator, x, z, |block| self.soil.contains(&block)) else {
+                continue;
+            };
+
+            let [x, y, z] = [x + off_x, y + off_y, z + off_z];
+
+            // trunk first so leaves wrap around it
+            for dy in 1..=height {
+                placements.push(Placement { pos: [x, y + dy, z], block: self.log, replaces: Block::AIR });
+            }
+
+            // wide layers below the top, narrow ones above
+            for dy in height - 1..=height + 2 {
+                let radius: i32 = if dy <= height { 2 } else { 1 };
+
+                for (dx, dz) in itertools::iproduct!(-radius..=radius, -radius..=radius) {
+                    let corner = dx.abs() == radius && dz.abs() == radius;
+
+                    // ragged wide corners, bare narrow ones
+                    if corner && (radius == 1 || rng.gen_bool(0.5)) {
+                        continue;
+                    }
+
+                    placements.push(Placement { pos: [x + dx, y + dy, z + dz], block: self.leaves, replaces: Block::AIR });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use crate::block::{Block, BlockRegistry, Fluid, FLUID_LEVELS};
+use crate::chunk::{offset, SIDES};
+
+// seconds between fluid steps
+pub const FLUID_TICK: f32 = 0.25;
+
+// most positions updated per step
+pub const MAX_UPDATES: usize = 4096;
+
+// next block at position from its surroundings, none if unchanged or unknown
+pub fn flow(
+    registry: &BlockRegistry,
+    pos:      [i32; 3],
+    block_at: impl Fn([i32; 3]) -> Option<Block>,
+) -> Option<Block> {
+    let block   = block_at(pos)?;
+    let current = registry.fluid(block);
+
+    // sources stay, fluids only fill air
+    if current.is_some_and(|fluid| fluid.is_source()) || (current.is_none() && !block.is_air()) {
+        return None;
+    }
+
+    let above = registry.fluid(block_at(offset(pos, [0, 1, 0]))?);
+
+    // falling fluid stays nearly full
+    let supply = if let Some(above) = above {
+        Some(Fluid { source: above.source, level: FLUID_LEVELS - 1 })
+    } else {
+        let mut supply: Option<Fluid> = None;
+
+        for side in SIDES {
+            let next = offset(pos, side);
+
+            let Some(fluid) = registry.fluid(block_at(next)?) else {
+                continue;
+            };
+
+            // flowing fluid only spreads once it cannot fall
+            let below = block_at(offset(next, [0, -1, 0]))?;
+
+            if !fluid.is_source() && (below.is_air() || registry.fluid(below).is_some()) {
+                continue;
+            }
+
+            let level = fluid.level - 1;
+
+            // strongest neighbour wins, lowest id on ties
+            let stronger = supply.is_none_or(|best| {
+                level > best.level || (level == best.level && fluid.source < best.source)
+            });
+
+            if level > 0 && stronger {
+                supply = Some(Fluid { source: fluid.source, level });
+            }
+        }
+
+        supply
+    };
+
+    // flowing fluid without supply drains away
+    let next = supply.map_or(Block::AIR, |fluid| registry.fluid_block(fluid.source, fluid.level));
+
+    (next != block).then_some(next)
+}
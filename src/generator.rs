@@ -0,0 +1,561 @@
+use crate::biome::Biome;
+use crate::block::{Block, BlockRegistry};
+use crate::chunk::{ChunkPos, CHUNK_SIZE};
+use crate::chunk_blocks::ChunkBlocks;
+use crate::seed::WorldSeed;
+
+use noise::permutationtable::PermutationTable;
+use noise::core::open_simplex::{open_simplex_2d, open_simplex_3d};
+
+use anyhow::Result;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+// climate noise frequency
+const CLIMATE_SCALE: f64 = 0.003;
+
+// climate noise stretch towards -1..1
+const CLIMATE_SPREAD: f64 = 2.0;
+
+// climate distance over which biomes blend
+const BLEND: f64 = 0.25;
+
+// mountain surface turning to snow
+const SNOW_LINE: i32 = 48;
+
+// highest water block of seas and lakes
+pub const SEA_LEVEL: i32 = -4;
+
+// surfaces up to this far above the sea become sand
+const BEACH: i32 = 1;
+
+// blocks between density samples
+const CELL: i32 = 4;
+
+// density noise channels: overhang, cave, two tunnel fields
+const CHANNELS: usize = 4;
+
+// density noise frequencies
+const OVERHANG_SCALE: f64 = 0.05;
+const CAVE_SCALE:     f64 = 0.04;
+const TUNNEL_SCALE:   f64 = 0.025;
+
+// solid blocks kept above caves
+const CRUST: i32 = 6;
+
+// underground carving and overhangs
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Caves {
+    // cave noise above which rock is carved, one or more disables caves
+    pub threshold: f64,
+
+    // tunnel noise band that is carved, zero disables tunnels
+    pub tunnels: f64,
+
+    // blocks the surface may shift by, zero disables overhangs
+    pub overhangs: f64,
+}
+
+impl Caves {
+    // flat heightmap without carving
+    pub const NONE: Caves = Caves { threshold: 1.0, tunnels: 0.0, overhangs: 0.0 };
+}
+
+impl Default for Caves {
+    // default carving
+    fn default() -> Self {
+        Self {
+            threshold: 0.9,
+            tunnels:   0.12,
+            overhangs: 5.0,
+        }
+    }
+}
+
+// terrain column at world position
+#[derive(Copy, Clone, Debug)]
+pub struct Column {
+    // surface height
+    pub height: i32,
+
+    // dominant biome
+    pub biome: Biome,
+}
+
+// resolved blocks of biome
+#[derive(Copy, Clone)]
+struct BiomeBlocks {
+    // top block
+    surface: Block,
+
+    // blocks below top
+    filler: Block,
+
+    // filler depth before stone
+    depth: i32,
+}
+
+// terrain generator
+pub struct Generator {
+    // simplex hashers
+    hashers: Vec<(u8, PermutationTable)>,
+
+    // noise divisor
+    divisor: f64,
+
+    // climate hashers
+    temperature: PermutationTable,
+    humidity:    PermutationTable,
+
+    // density hashers per channel
+    density: [PermutationTable; CHANNELS],
+
+    // carving settings
+    caves: Caves,
+
+    // blocks per biome
+    biomes: [BiomeBlocks; Biome::ALL.len()],
+
+    // deepest filler of any biome
+    depth: i32,
+
+    // deep, peak and shore blocks
+    stone: Block,
+    snow:  Block,
+    sand:  Block,
+
+    // sea fill
+    water: Block,
+}
+
+impl Generator {
+    // create generator
+    pub fn new(seed: WorldSeed, registry: &BlockRegistry) -> Result<Self> {
+        Self::with_caves(seed, registry, Caves::default())
+    }
+
+    // create generator with carving settings
+    pub fn with_caves(seed: WorldSeed, registry: &BlockRegistry, caves: Caves) -> Result<Self> {
+        let hashers = seed.hashers();
+
+        // get hashers sum
+        let divisor =
+            hashers
+                .iter()
+                .map(|(n, _)| 1 / n)
+                .sum::<u8>() as f64;
+
+        // resolve biome block names
+        let mut biomes = [BiomeBlocks { surface: Block::AIR, filler: Block::AIR, depth: 0 }; Biome::ALL.len()];
+
+        for biome in Biome::ALL {
+            let layers = biome.layers();
+
+            biomes[biome as usize] = BiomeBlocks {
+                surface: registry.require(layers.surface)?,
+                filler:  registry.require(layers.filler)?,
+                depth:   layers.depth,
+            };
+        }
+
+        Ok(Self {
+            hashers,
+            divisor,
+
+            temperature: PermutationTable::new(seed.derive("temperature") as u32),
+            humidity:    PermutationTable::new(seed.derive("humidity") as u32),
+
+            density: ["overhang", "cave", "tunnel_a", "tunnel_b"].map(|stream| {
+                PermutationTable::new(seed.derive(stream) as u32)
+            }),
+
+            caves,
+
+            depth: biomes.iter().map(|blocks| blocks.depth).max().unwrap_or(0),
+            biomes,
+
+            stone: registry.require("stone")?,
+            snow:  registry.require("snow")?,
+            sand:  registry.require("sand")?,
+
+            water: registry.require("water")?,
+        })
+    }
+
+    // raw terrain noise in -1..1
+    fn raw_height(&self, x: i32, z: i32) -> f64 {
+        self.hashers
+            .iter()
+            .map(|(n, hasher)| {
+                open_simplex_2d([
+                    (x as f32 * *n as f32 * 0.025) as f64,
+                    (z as f32 * *n as f32 * 0.025) as f64,
+                ], hasher) / *n as f64
+            }).sum::<f64>() / self.divisor
+    }
+
+    // two octave climate noise
+    fn climate_noise(x: i32, z: i32, hasher: &PermutationTable) -> f64 {
+        let sample = |scale: f64| open_simplex_2d([x as f64 * scale, z as f64 * scale], hasher);
+        let value  = sample(CLIMATE_SCALE) + sample(CLIMATE_SCALE * 4.0) / 4.0;
+
+        (value / 1.25 * CLIMATE_SPREAD).clamp(-1.0, 1.0)
+    }
+
+    // temperature and humidity at world position
+    pub fn climate(&self, x: i32, z: i32) -> [f64; 2] {
+        [
+            Self::climate_noise(x, z, &self.temperature),
+            Self::climate_noise(x, z, &self.humidity),
+        ]
+    }
+
+    // terrain column at world position
+    pub fn column(&self, x: i32, z: i32) -> Column {
+        let [temperature, humidity] = self.climate(x, z);
+        let raw = self.raw_height(x, z);
+
+        // weight biomes by climate distance
+        let weights = Biome::ALL.map(|biome| {
+            let [t, h] = biome.climate();
+            let dist   = (temperature - t).powi(2) + (humidity - h).powi(2);
+
+            (-dist / (BLEND * BLEND)).exp()
+        });
+
+        let total = weights.iter().sum::<f64>();
+
+        // blend heights so borders stay smooth
+        let height =
+            Biome::ALL
+                .iter()
+                .zip(weights)
+                .map(|(biome, weight)| biome.curve().height(raw) * weight)
+                .sum::<f64>() / total;
+
+        let biome =
+            Biome::ALL
+                .into_iter()
+                .zip(weights)
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(biome, _)| biome)
+                .unwrap();
+
+        Column { height: height.floor() as i32, biome }
+    }
+
+    // terrain surface height at world position
+    pub fn height(&self, x: i32, z: i32) -> i32 {
+        self.column(x, z).height
+    }
+
+    // dominant biome at world position
+    pub fn biome(&self, x: i32, z: i32) -> Biome {
+        self.column(x, z).biome
+    }
+
+    // carving settings
+    pub fn caves(&self) -> Caves {
+        self.caves
+    }
+
+    // density noise at lattice point
+    fn lattice(&self, lx: i32, ly: i32, lz: i32) -> [f64; CHANNELS] {
+        let [x, y, z] = [lx, ly, lz].map(|coord| (coord * CELL) as f64);
+        let [overhang, cave, tunnel_a, tunnel_b] = &self.density;
+
+        let values = [
+            open_simplex_3d([x * OVERHANG_SCALE, y * OVERHANG_SCALE, z * OVERHANG_SCALE], overhang),
+
+            // flattened caves
+            open_simplex_3d([x * CAVE_SCALE, y * CAVE_SCALE * 2.0, z * CAVE_SCALE], cave),
+
+            open_simplex_3d([x * TUNNEL_SCALE, y * TUNNEL_SCALE, z * TUNNEL_SCALE], tunnel_a),
+            open_simplex_3d([x * TUNNEL_SCALE, y * TUNNEL_SCALE, z * TUNNEL_SCALE], tunnel_b),
+        ];
+
+        // 3d simplex stays within about half of -1..1
+        values.map(|value| (value * 2.0).clamp(-1.0, 1.0))
+    }
+
+    // density noise in lattice layer at column, interpolated x then z
+    fn plane(x: i32, z: i32, ly: i32, lattice: impl Fn(i32, i32, i32) -> [f64; CHANNELS]) -> [f64; CHANNELS] {
+        let (lx, lz) = (x.div_euclid(CELL), z.div_euclid(CELL));
+        let [fx, fz] = [x, z].map(|coord| coord.rem_euclid(CELL) as f64 / CELL as f64);
+
+        let near = lerp(lattice(lx, ly, lz),     lattice(lx + 1, ly, lz),     fx);
+        let far  = lerp(lattice(lx, ly, lz + 1), lattice(lx + 1, ly, lz + 1), fx);
+
+        lerp(near, far, fz)
+    }
+
+    // density noise at world position, interpolated between lattice points
+    fn channels(x: i32, y: i32, z: i32, lattice: impl Fn(i32, i32, i32) -> [f64; CHANNELS]) -> [f64; CHANNELS] {
+        let (ly, fy) = (y.div_euclid(CELL), y.rem_euclid(CELL) as f64 / CELL as f64);
+        lerp(Self::plane(x, z, ly, &lattice), Self::plane(x, z, ly + 1, &lattice), fy)
+    }
+
+    // check if terrain fills position before carving
+    fn filled(&self, column: &Column, y: i32, channels: &[f64; CHANNELS]) -> bool {
+        (column.height - y) as f64 + self.caves.overhangs * channels[0] >= 0.0
+    }
+
+    // check if caves or tunnels remove position
+    fn carved(&self, column: &Column, y: i32, channels: &[f64; CHANNELS]) -> bool {
+        let cave   = y < column.height - CRUST && channels[1] > self.caves.threshold;
+        let tunnel = channels[2].abs() < self.caves.tunnels && channels[3].abs() < self.caves.tunnels;
+
+        cave || tunnel
+    }
+
+    // block in column given filled blocks directly above
+    fn layer(&self, column: &Column, y: i32, cover: i32) -> Block {
+        let blocks = &self.biomes[column.biome as usize];
+
+        match cover {
+            // peaks are snowy regardless of biome
+            0 if y >= SNOW_LINE => self.snow,
+
+            // shores and sea floors
+            0 if y <= SEA_LEVEL + BEACH => self.sand,
+
+            0                          => blocks.surface,
+            _ if cover <= blocks.depth => blocks.filler,
+            _                          => self.stone,
+        }
+    }
+
+    // block of unfilled position, flooded below sea level
+    fn open(&self, y: i32) -> Block {
+        if y <= SEA_LEVEL {
+            self.water
+        } else {
+            Block::AIR
+        }
+    }
+
+    // block at world position
+    pub fn block_at(&self, x: i32, y: i32, z: i32) -> Block {
+        self.sample(&self.column(x, z), x, y, z, |lx, ly, lz| self.lattice(lx, ly, lz))
+    }
+
+    // block at world position in column, given noise lattice
+    fn sample(
+        &self,
+        column:  &Column,
+        x:       i32,
+        y:       i32,
+        z:       i32,
+        lattice: impl Fn(i32, i32, i32) -> [f64; CHANNELS],
+    ) -> Block {
+        let channels = Self::channels(x, y, z, &lattice);
+
+        if !self.filled(column, y, &channels) {
+            return self.open(y);
+        }
+
+        if self.carved(column, y, &channels) {
+            return Block::AIR;
+        }
+
+        // count filled blocks above for surface layers
+        let cover =
+            (1..=self.depth + 1)
+                .take_while(|dy| self.filled(column, y + dy, &Self::channels(x, y + dy, z, &lattice)))
+                .count() as i32;
+
+        self.layer(column, y, cover)
+    }
+
+    // height above highest block in column
+    pub fn surface(&self, x: i32, z: i32) -> i32 {
+        let top = (self.height(x, z) + self.caves.overhangs.abs().ceil() as i32).max(SEA_LEVEL);
+
+        (top - 2 * CRUST..=top)
+            .rev()
+            .find(|&y| self.block_at(x, y, z) != Block::AIR)
+            .unwrap_or(top - 2 * CRUST) + 1
+    }
+
+    // highest block column may hold
+    pub fn top(&self, x: i32, z: i32) -> i32 {
+        self.column_top(&self.column(x, z))
+    }
+
+    // blocks of chunk, none if above terrain
+    pub fn generate(&self, pos: ChunkPos) -> Option<ChunkBlocks> {
+        self.generate_with_tops(pos).0
+    }
+
+    // highest blocks the columns of chunk may hold, indexed by z then x
+    pub fn tops(&self, pos: ChunkPos) -> Vec<i32> {
+        let size = CHUNK_SIZE as i32;
+
+        itertools::iproduct!(0..size, 0..size)
+            .map(|(z, x)| self.column(x + pos.0 * size, z + pos.2 * size))
+            .map(|column| self.column_top(&column))
+            .collect()
+    }
+
+    // highest block sampled column may hold
+    fn column_top(&self, column: &Column) -> i32 {
+        column.height + self.caves.overhangs.abs().ceil() as i32
+    }
+
+    // noise around chunk for sampling its blocks, given highest blocks of its columns
+    pub fn terrain(&self, pos: ChunkPos, tops: &[i32]) -> Terrain<'_> {
+        let size = CHUNK_SIZE as i32;
+        let off  = [pos.0 * size, pos.1 * size, pos.2 * size];
+
+        // keep sections above terrain and sea empty
+        let empty = off[1] > SEA_LEVEL && tops.iter().all(|&top| top < off[1]);
+
+        // lattice around chunk, including blocks above for cover
+        let top   = size + self.depth + 1;
+        let start = off.map(|off| off.div_euclid(CELL));
+        let dims  = [size, top, size].map(|len| (len / CELL + 2) as usize);
+
+        // noise sampled once columns need it
+        let grid = RefCell::new(vec![None; if empty { 0 } else { dims.iter().product() }]);
+
+        Terrain { generator: self, off, empty, start, dims, grid, columns: RefCell::new(HashMap::new()) }
+    }
+
+    // blocks of chunk with highest blocks its columns may hold, indexed by z then x
+    pub fn generate_with_tops(&self, pos: ChunkPos) -> (Option<ChunkBlocks>, Vec<i32>) {
+        let size  = CHUNK_SIZE as i32;
+        let off_x = pos.0 * size;
+        let off_z = pos.2 * size;
+
+        // sample noise for columns
+        let columns =
+            itertools::iproduct!(0..size, 0..size)
+                .map(|(z, x)| self.column(x + off_x, z + off_z))
+                .collect::<Vec<_>>();
+
+        let tops    = columns.iter().map(|column| self.column_top(column)).collect::<Vec<_>>();
+        let terrain = self.terrain(pos, &tops);
+
+        if terrain.is_empty() {
+            return (None, tops);
+        }
+
+        let blocks =
+            itertools::iproduct!(0..size, 0..size)
+                .zip(&columns)
+                .flat_map(|((z, x), column)| terrain.column_blocks(x, z, column))
+                .collect();
+
+        (Some(blocks), tops)
+    }
+}
+
+// noise lattice around chunk, sampling its blocks by column
+pub struct Terrain<'a> {
+    // terrain generator
+    generator: &'a Generator,
+
+    // chunk origin
+    off: [i32; 3],
+
+    // above terrain and sea
+    empty: bool,
+
+    // lattice cells around chunk and their noise
+    start: [i32; 3],
+    dims:  [usize; 3],
+    grid:  RefCell<Vec<Option<[f64; CHANNELS]>>>,
+
+    // local columns sampled so far
+    columns: RefCell<HashMap<(i32, i32), Column>>,
+}
+
+impl Terrain<'_> {
+    // check if chunk holds only open blocks
+    pub fn is_empty(&self) -> bool {
+        self.empty
+    }
+
+    // block at local position, sampling only the noise it needs
+    pub fn block(&self, x: i32, y: i32, z: i32) -> Block {
+        let [off_x, off_y, off_z] = self.off;
+
+        if self.empty {
+            return self.generator.open(y + off_y);
+        }
+
+        let column =
+            *self.columns
+                .borrow_mut()
+                .entry((x, z))
+                .or_insert_with(|| self.generator.column(x + off_x, z + off_z));
+
+        self.generator.sample(&column, x + off_x, y + off_y, z + off_z, |lx, ly, lz| self.lattice(lx, ly, lz))
+    }
+
+    // noise at lattice point, sampled on first use
+    fn lattice(&self, lx: i32, ly: i32, lz: i32) -> [f64; CHANNELS] {
+        let [x, y, z] = [lx - self.start[0], ly - self.start[1], lz - self.start[2]].map(|coord| coord as usize);
+
+        *self.grid.borrow_mut()[(z * self.dims[0] + x) * self.dims[1] + y]
+            .get_or_insert_with(|| self.generator.lattice(lx, ly, lz))
+    }
+
+    // blocks of local column bottom to top
+    fn column_blocks(&self, x: i32, z: i32, column: &Column) -> Vec<Block> {
+        let generator = self.generator;
+        let size      = CHUNK_SIZE as i32;
+        let top       = size + generator.depth + 1;
+
+        let [off_x, off_y, off_z] = self.off;
+        let (x, z)                = (x + off_x, z + off_z);
+
+        // lattice layers at column, then interpolated along y
+        let planes =
+            (0..self.dims[1] as i32)
+                .map(|ly| Generator::plane(x, z, self.start[1] + ly, |lx, ly, lz| self.lattice(lx, ly, lz)))
+                .collect::<Vec<_>>();
+
+        let channels =
+            (off_y..off_y + top)
+                .map(|y| {
+                    let ly = (y.div_euclid(CELL) - self.start[1]) as usize;
+                    lerp(planes[ly], planes[ly + 1], y.rem_euclid(CELL) as f64 / CELL as f64)
+                }).collect::<Vec<_>>();
+
+        let filled =
+            channels
+                .iter()
+                .enumerate()
+                .map(|(y, channels)| generator.filled(column, y as i32 + off_y, channels))
+                .collect::<Vec<_>>();
+
+        (0..size as usize).map(|y| {
+            let world_y = y as i32 + off_y;
+
+            if !filled[y] {
+                return generator.open(world_y);
+            }
+
+            if generator.carved(column, world_y, &channels[y]) {
+                return Block::AIR;
+            }
+
+            // count filled blocks above for surface layers
+            let cover =
+                filled[y + 1..]
+                    .iter()
+                    .take(generator.depth as usize + 1)
+                    .take_while(|&&filled| filled)
+                    .count() as i32;
+
+            generator.layer(column, world_y, cover)
+        }).collect()
+    }
+}
+
+// interpolate noise channels
+fn lerp(a: [f64; CHANNELS], b: [f64; CHANNELS], t: f64) -> [f64; CHANNELS] {
+    std::array::from_fn(|channel| a[channel] + (b[channel] - a[channel]) * t)
+}
@@ -0,0 +1,227 @@
+use crate::chunk::ChunkPos;
+use crate::seed::WorldSeed;
+use crate::storage::{ChunkStorage, SavedChunk};
+
+use anyhow::{Result, Context, anyhow};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+use web_sys::{IdbDatabase, IdbKeyRange, IdbObjectStore, IdbRequest, IdbTransactionMode};
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+// database name
+const DATABASE: &str = "blocks";
+
+// database schema version
+const DATABASE_VERSION: u32 = 1;
+
+// object stores
+const CHUNKS: &str = "chunks";
+const SEEDS:  &str = "seeds";
+
+// chunks saved in browser database
+pub struct IndexedDbStorage {
+    // open database
+    db: IdbDatabase,
+
+    // world key prefix
+    world: String,
+
+    // saved chunks, read upfront
+    chunks: RefCell<HashMap<ChunkPos, Vec<u8>>>,
+
+    // saved seed
+    seed: Cell<Option<WorldSeed>>,
+}
+
+impl IndexedDbStorage {
+    // open world in database
+    pub async fn open(world: &str) -> Result<Self> {
+        let factory =
+            web_sys::window()
+                .context("missing window")?
+                .indexed_db()
+                .map_err(js_error)?
+                .context("indexeddb unavailable")?;
+
+        let request = factory.open_with_u32(DATABASE, DATABASE_VERSION).map_err(js_error)?;
+
+        // create stores on first open
+        let upgrade = Closure::<dyn FnMut(JsValue)>::new({
+            let request = request.clone();
+
+            move |_| {
+                let Ok(db) = request.result().and_then(|db| db.dyn_into::<IdbDatabase>()) else {
+                    return;
+                };
+
+                for store in [CHUNKS, SEEDS] {
+                    if !db.object_store_names().contains(store) {
+                        let _ = db.create_object_store(store);
+                    }
+                }
+            }
+        });
+
+        request.set_onupgradeneeded(Some(upgrade.as_ref().unchecked_ref()));
+
+        let db: IdbDatabase = wait(&request).await?.dyn_into().map_err(js_error)?;
+        request.set_onupgradeneeded(None);
+
+        let storage = Self {
+            db,
+            world:  world.to_string(),
+            chunks: RefCell::new(HashMap::new()),
+            seed:   Cell::new(None),
+        };
+
+        storage.preload().await?;
+        Ok(storage)
+    }
+
+    // read saved world into memory
+    async fn preload(&self) -> Result<()> {
+        let transaction =
+            self.db
+                .transaction_with_str_sequence(&js_sys::Array::of2(&CHUNKS.into(), &SEEDS.into()))
+                .map_err(js_error)?;
+
+        let chunks = transaction.object_store(CHUNKS).map_err(js_error)?;
+        let seeds  = transaction.object_store(SEEDS).map_err(js_error)?;
+
+        // keys within world
+        let range = IdbKeyRange::bound(
+            &format!("{}/", self.world).into(),
+            &format!("{}/\u{ffff}", self.world).into(),
+        ).map_err(js_error)?;
+
+        let keys   = chunks.get_all_keys_with_key(&range).map_err(js_error)?;
+        let values = chunks.get_all_with_key(&range).map_err(js_error)?;
+        let seed   = seeds.get(&self.world.as_str().into()).map_err(js_error)?;
+
+        let keys:   js_sys::Array = wait(&keys).await?.dyn_into().map_err(js_error)?;
+        let values: js_sys::Array = wait(&values).await?.dyn_into().map_err(js_error)?;
+        let seed = wait(&seed).await?;
+
+        let mut loaded = self.chunks.borrow_mut();
+
+        for (key, value) in keys.iter().zip(values.iter()) {
+            let Some(pos) = key.as_string().and_then(|key| Self::parse_key(&key)) else {
+                continue;
+            };
+
+            loaded.insert(pos, js_sys::Uint8Array::new(&value).to_vec());
+        }
+
+        // seeds stored as text to keep all bits
+        if let Some(seed) = seed.as_string() {
+            self.seed.set(Some(WorldSeed::new(seed.parse().context("invalid saved seed")?)));
+        }
+
+        Ok(())
+    }
+
+    // database key of chunk
+    fn key(&self, pos: ChunkPos) -> String {
+        format!("{}/{}.{}.{}", self.world, pos.0, pos.1, pos.2)
+    }
+
+    // chunk position of key
+    fn parse_key(key: &str) -> Option<ChunkPos> {
+        let (_, pos)   = key.rsplit_once('/')?;
+        let mut coords = pos.split('.').map(|coord| coord.parse().ok());
+        let pos        = (coords.next()??, coords.next()??, coords.next()??);
+
+        coords.next().is_none().then_some(pos)
+    }
+
+    // store for writing
+    fn store(&self, name: &str) -> Result<IdbObjectStore> {
+        self.db
+            .transaction_with_str_and_mode(name, IdbTransactionMode::Readwrite)
+            .and_then(|transaction| transaction.object_store(name))
+            .map_err(js_error)
+    }
+}
+
+impl ChunkStorage for IndexedDbStorage {
+    // read preloaded chunk
+    fn read(&self, pos: ChunkPos) -> Result<Option<Vec<u8>>> {
+        Ok(self.chunks.borrow().get(&pos).cloned())
+    }
+
+    // write chunk in background
+    fn write(&self, pos: ChunkPos, data: &[u8]) -> Result<()> {
+        self.store(CHUNKS)?
+            .put_with_key(&js_sys::Uint8Array::from(data), &self.key(pos).into())
+            .map_err(js_error)?;
+
+        self.chunks.borrow_mut().insert(pos, data.to_vec());
+        Ok(())
+    }
+
+    // read preloaded seed
+    fn read_seed(&self) -> Result<Option<WorldSeed>> {
+        Ok(self.seed.get())
+    }
+
+    // write seed in background
+    fn write_seed(&self, seed: WorldSeed) -> Result<()> {
+        self.store(SEEDS)?
+            .put_with_key(&seed.to_string().into(), &self.world.as_str().into())
+            .map_err(js_error)?;
+
+        self.seed.set(Some(seed));
+        Ok(())
+    }
+
+    // preloaded chunks
+    fn entries(&self) -> Result<Vec<SavedChunk>> {
+        Ok(self.chunks.borrow().iter().map(|(pos, data)| (*pos, data.clone())).collect())
+    }
+}
+
+// wait for database request
+async fn wait(request: &IdbRequest) -> Result<JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+
+    JsFuture::from(promise).await.map_err(js_error)?;
+
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+
+    request.result().map_err(js_error)
+}
+
+// offer file download
+pub fn download(name: &str, data: &[u8]) -> Result<()> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(data));
+    let blob  = web_sys::Blob::new_with_u8_array_sequence(&parts).map_err(js_error)?;
+    let url   = web_sys::Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+
+    let link: web_sys::HtmlAnchorElement =
+        web_sys::window()
+            .and_then(|win| win.document())
+            .context("missing document")?
+            .create_element("a")
+            .map_err(js_error)?
+            .dyn_into()
+            .map_err(js_error)?;
+
+    link.set_href(&url);
+    link.set_download(name);
+    link.click();
+
+    web_sys::Url::revoke_object_url(&url).map_err(js_error)
+}
+
+// convert javascript error
+fn js_error(err: impl Into<JsValue>) -> anyhow::Error {
+    anyhow!("{:?}", err.into())
+}
@@ -8,20 +8,26 @@ pub struct Instance {
     // position
     pos: [f32; 4],
 
-    // texture
-    tex: f32,
+    // face tiles, two per word
+    tiles: [u32; 3],
 
     // rendered faces
     faces: u32,
+
+    // packed face light, four faces per word
+    light: [u32; 2],
+
+    // face corner brightness, two bits per corner and four faces per word
+    ao: [u32; 2],
 }
 
 impl Instance {
     // layout attributes
-    const ATTRS: [wgpu::VertexAttribute; 3] =
-        wgpu::vertex_attr_array![3 => Float32x4, 4 => Float32, 5 => Uint32];
+    const ATTRS: [wgpu::VertexAttribute; 5] =
+        wgpu::vertex_attr_array![3 => Float32x4, 4 => Uint32x3, 5 => Uint32, 6 => Uint32x2, 7 => Uint32x2];
 
     // create instance
-    pub fn new(pos: [f32; 3], tex: f32, faces: [bool; 6]) -> Self {
+    pub fn new(pos: [f32; 3], tiles: [u16; 6], faces: [bool; 6], light: [u8; 6], ao: [[u8; 4]; 6]) -> Self {
         // convert bools into binary
         let faces_bin =
             faces
@@ -35,13 +41,52 @@ impl Instance {
                     }
                 });
 
+        // pack tiles into words
+        let tiles = [0, 1, 2].map(|word| {
+            tiles[word * 2] as u32 | (tiles[word * 2 + 1] as u32) << 16
+        });
+
+        // pack face light into words
+        let light = [0, 1].map(|word| {
+            light[word * 4..(word * 4 + 4).min(6)]
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (face, level)| acc | (*level as u32) << (face * 8))
+        });
+
+        // pack corner brightness into words
+        let ao = [0, 1].map(|word| {
+            ao[word * 4..(word * 4 + 4).min(6)]
+                .iter()
+                .flatten()
+                .enumerate()
+                .fold(0, |acc, (corner, level)| acc | (*level as u32) << (corner * 2))
+        });
+
         Self {
-            tex,
+            tiles,
+            light,
+            ao,
             pos:   [pos[0], pos[1], pos[2], 0.0],
             faces: faces_bin,
         }
     }
 
+    // rendered faces
+    pub fn faces(&self) -> u32 {
+        self.faces
+    }
+
+    // packed light of face
+    pub fn light(&self, face: usize) -> u8 {
+        (self.light[face / 4] >> (face % 4 * 8)) as u8
+    }
+
+    // brightness of face corner
+    pub fn ao(&self, face: usize, corner: usize) -> u8 {
+        (self.ao[face / 4] >> ((face % 4 * 4 + corner) * 2)) as u8 & 3
+    }
+
     // memory layout
     pub fn layout() -> wgpu::VertexBufferLayout<'static> {
         wgpu::VertexBufferLayout {
@@ -7,4 +7,25 @@ pub mod projection;
 pub mod camera_controller;
 pub mod instance;
 pub mod chunk;
+pub mod chunk_blocks;
 pub mod world;
+pub mod seed;
+pub mod options;
+pub mod mesh;
+pub mod chunk_buffers;
+pub mod block;
+pub mod generator;
+pub mod biome;
+pub mod decorator;
+pub mod fluid;
+pub mod light;
+pub mod sky;
+pub mod raycast;
+pub mod aabb;
+pub mod outline;
+pub mod player;
+pub mod storage;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod region;
+#[cfg(target_arch = "wasm32")]
+pub mod indexed_db;
@@ -0,0 +1,547 @@
+use crate::block::{Block, BlockRegistry};
+use crate::chunk::{self, Chunk, ChunkPos, CHUNK_SIZE, NEIGHBOURS, SIDES};
+use crate::generator::Generator;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// brightest light level
+pub const MAX_LIGHT: u8 = 15;
+
+// blocks along chunk edge
+const SIZE: i32 = CHUNK_SIZE as i32;
+
+// blocks per chunk
+const VOLUME: usize = (SIZE * SIZE * SIZE) as usize;
+
+// offsets straight up and down
+const UP:   [i32; 3] = [0, 1, 0];
+const DOWN: [i32; 3] = [0, -1, 0];
+
+// light from the sky or from emissive blocks
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Channel {
+    Sky,
+    Block,
+}
+
+impl Channel {
+    // both channels
+    pub const ALL: [Channel; 2] = [Channel::Sky, Channel::Block];
+
+    // bit offset in packed light
+    fn shift(self) -> u8 {
+        match self {
+            Channel::Sky   => 4,
+            Channel::Block => 0,
+        }
+    }
+}
+
+// light levels of chunk, sky light in the high nibble
+#[derive(Clone, Debug)]
+pub struct ChunkLight {
+    // packed light of every block while uniform
+    fill: u8,
+
+    // packed light per block once it differs
+    values: Vec<u8>,
+}
+
+impl ChunkLight {
+    // same light everywhere
+    pub fn uniform(sky: u8, block: u8) -> Self {
+        Self { fill: sky << 4 | block, values: Vec::new() }
+    }
+
+    // packed light at block index
+    fn packed(&self, index: usize) -> u8 {
+        if self.values.is_empty() {
+            self.fill
+        } else {
+            self.values[index]
+        }
+    }
+
+    // light of channel at block index
+    pub fn get(&self, index: usize, channel: Channel) -> u8 {
+        self.packed(index) >> channel.shift() & 0xf
+    }
+
+    // sky and block light at block index
+    pub fn levels(&self, index: usize) -> [u8; 2] {
+        Channel::ALL.map(|channel| self.get(index, channel))
+    }
+
+    // set light of channel at block index, returning bytes allocated
+    pub fn set(&mut self, index: usize, channel: Channel, level: u8) -> usize {
+        let mask  = 0xf << channel.shift();
+        let value = self.packed(index) & !mask | level << channel.shift();
+
+        if !self.values.is_empty() {
+            self.values[index] = value;
+            return 0;
+        }
+
+        if value == self.fill {
+            return 0;
+        }
+
+        // first differing block unpacks every block
+        self.values        = vec![self.fill; VOLUME];
+        self.values[index] = value;
+
+        VOLUME
+    }
+
+    // light of every block if uniform
+    pub fn single(&self) -> Option<[u8; 2]> {
+        self.values.is_empty().then(|| self.levels(0))
+    }
+
+    // estimated memory in bytes
+    pub fn memory(&self) -> usize {
+        self.values.len()
+    }
+}
+
+impl Default for ChunkLight {
+    // dark chunk
+    fn default() -> Self {
+        Self::uniform(0, 0)
+    }
+}
+
+// light propagation through loaded chunks
+pub struct Lighting<'a> {
+    // loaded chunks
+    chunks: &'a mut HashMap<ChunkPos, Chunk>,
+
+    // block types
+    registry: &'a BlockRegistry,
+
+    // terrain for sky above unloaded chunks
+    generator: &'a Generator,
+
+    // above highest block
+    max_y: i32,
+
+    // positions to spread light from, per channel
+    spread: [VecDeque<[i32; 3]>; 2],
+
+    // darkened positions with their previous level, per channel
+    darkened: [VecDeque<([i32; 3], u8)>; 2],
+
+    // chunks showing changed light
+    pub touched: HashSet<ChunkPos>,
+
+    // bytes allocated for light
+    pub grown: usize,
+}
+
+impl<'a> Lighting<'a> {
+    // create propagation over chunks
+    pub fn new(
+        chunks:    &'a mut HashMap<ChunkPos, Chunk>,
+        registry:  &'a BlockRegistry,
+        generator: &'a Generator,
+        max_y:     i32,
+    ) -> Self {
+        Self {
+            chunks,
+            registry,
+            generator,
+            max_y,
+
+            spread:   Default::default(),
+            darkened: Default::default(),
+
+            touched: HashSet::new(),
+            grown:   0,
+        }
+    }
+
+    // chunk and local position of world position
+    fn locate([x, y, z]: [i32; 3]) -> (ChunkPos, [usize; 3]) {
+        (
+            (x.div_euclid(SIZE), y.div_euclid(SIZE), z.div_euclid(SIZE)),
+            [x, y, z].map(|coord| coord.rem_euclid(SIZE) as usize),
+        )
+    }
+
+    // block and light at world position, none if not loaded
+    fn cell(&self, pos: [i32; 3]) -> Option<(Block, [u8; 2])> {
+        let (chunk, [x, y, z]) = Self::locate(pos);
+        let chunk = self.chunks.get(&chunk)?;
+
+        Some((chunk.block(x, y, z), chunk.light(x, y, z)))
+    }
+
+    // set light at world position, marking chunks that show it
+    fn set(&mut self, pos: [i32; 3], channel: Channel, level: u8) {
+        let (chunk_pos, [x, y, z]) = Self::locate(pos);
+
+        let Some(chunk) = self.chunks.get_mut(&chunk_pos) else {
+            return;
+        };
+
+        self.grown += chunk.set_light(x, y, z, channel, level);
+        self.touched.insert(chunk_pos);
+
+        Self::mark_border(&mut self.touched, chunk_pos, [x, y, z]);
+    }
+
+    // mark neighbours whose faces look into border block
+    fn mark_border(touched: &mut HashSet<ChunkPos>, chunk_pos: ChunkPos, local: [usize; 3]) {
+        let last = SIZE as usize - 1;
+
+        for (axis, local) in local.into_iter().enumerate() {
+            let mut next = [chunk_pos.0, chunk_pos.1, chunk_pos.2];
+
+            if local == 0 {
+                next[axis] -= 1;
+            } else if local == last {
+                next[axis] += 1;
+            } else {
+                continue;
+            }
+
+            touched.insert((next[0], next[1], next[2]));
+        }
+    }
+
+    // check if unloaded position is open to the sky
+    fn open_sky(&self, [x, y, z]: [i32; 3]) -> bool {
+        y >= self.max_y || y > self.generator.top(x, z)
+    }
+
+    // light newly loaded chunk from highest blocks of its columns, exchanging light with neighbours
+    pub fn light_chunk(&mut self, pos: ChunkPos, tops: &[i32]) {
+        let Some(chunk) = self.chunks.get(&pos) else {
+            return;
+        };
+
+        let origin = chunk_origin(pos);
+
+        // sky shining into columns from above, unless the chunk above passes it on
+        let above  = origin[1] + SIZE;
+        let loaded = self.chunks.contains_key(&chunk_offset(pos, UP));
+
+        let open =
+            itertools::iproduct!(0..SIZE, 0..SIZE)
+                .filter(|&(x, z)| !loaded && (above >= self.max_y || above > tops[(z * SIZE + x) as usize]))
+                .collect::<Vec<_>>();
+
+        let emissive = chunk.blocks().palette().iter().any(|&block| self.registry.get(block).emissive > 0);
+
+        if chunk.is_empty() && open.len() == (SIZE * SIZE) as usize {
+            // open air only needs its borders spread
+            self.chunks.get_mut(&pos).unwrap().set_lighting(ChunkLight::uniform(MAX_LIGHT, 0));
+
+            for offset in NEIGHBOURS {
+                self.queue_border(pos, offset);
+            }
+        } else {
+            // lowest lit block of each column, size if unlit
+            let mut floors = vec![SIZE; (SIZE * SIZE) as usize];
+            let chunk      = self.chunks.get_mut(&pos).unwrap();
+
+            // straight down until blocked or dimmed
+            for &(x, z) in &open {
+                for y in (0..SIZE).rev() {
+                    let block = chunk.block(x as usize, y as usize, z as usize);
+
+                    if !self.registry.get(block).transparent {
+                        break;
+                    }
+
+                    let level = if block.is_air() { MAX_LIGHT } else { MAX_LIGHT - 1 };
+
+                    self.grown += chunk.set_light(x as usize, y as usize, z as usize, Channel::Sky, level);
+                    floors[(z * SIZE + x) as usize] = y;
+
+                    if !block.is_air() {
+                        break;
+                    }
+                }
+            }
+
+            // columns beyond the chunk count as unlit
+            let floor = |x: i32, z: i32| {
+                if (0..SIZE).contains(&x) && (0..SIZE).contains(&z) { floors[(z * SIZE + x) as usize] } else { SIZE }
+            };
+
+            // only the outside of lit columns spreads further
+            for &(x, z) in &open {
+                for y in floor(x, z)..SIZE {
+                    let outside = y == floor(x, z) || SIDES.iter().any(|side| floor(x + side[0], z + side[2]) > y);
+
+                    if outside {
+                        self.spread[Channel::Sky as usize].push_back([origin[0] + x, origin[1] + y, origin[2] + z]);
+                    }
+                }
+            }
+        }
+
+        self.touched.insert(pos);
+        self.touched.extend(NEIGHBOURS.map(|offset| chunk_offset(pos, offset)));
+
+        // emissive blocks light their surroundings
+        if emissive {
+            for (x, y, z) in itertools::iproduct!(0..SIZE, 0..SIZE, 0..SIZE) {
+                let pos   = [origin[0] + x, origin[1] + y, origin[2] + z];
+                let level = self.cell(pos).map_or(0, |(block, _)| self.registry.get(block).emissive);
+
+                if level > 0 {
+                    self.set(pos, Channel::Block, level);
+                    self.spread[Channel::Block as usize].push_back(pos);
+                }
+            }
+        }
+
+        // light of neighbours flows in
+        for offset in NEIGHBOURS {
+            self.queue_border(chunk_offset(pos, offset), offset.map(|dir| -dir));
+        }
+
+        self.propagate();
+
+        // sky assumed to reach the chunk below may be blocked by this one
+        let below_pos = chunk_offset(pos, DOWN);
+
+        if let (Some(chunk), Some(below)) = (self.chunks.get(&pos), self.chunks.get(&below_pos)) {
+            let below_origin = chunk_origin(below_pos);
+
+            let blocked =
+                face(DOWN)
+                    .filter(|&(bottom, top)| {
+                        below.light(top[0], top[1], top[2])[0] == MAX_LIGHT
+                            && chunk.light(bottom[0], bottom[1], bottom[2])[0] < MAX_LIGHT
+                    })
+                    .map(|(_, top)| [0, 1, 2].map(|axis| below_origin[axis] + top[axis] as i32))
+                    .collect::<Vec<_>>();
+
+            for below in blocked {
+                self.set(below, Channel::Sky, 0);
+                self.darkened[Channel::Sky as usize].push_back((below, MAX_LIGHT));
+            }
+        }
+
+        self.propagate();
+    }
+
+    // queue border blocks of chunk whose light would brighten the neighbour across offset
+    fn queue_border(&mut self, pos: ChunkPos, offset: [i32; 3]) {
+        let next_pos = chunk_offset(pos, offset);
+
+        let (Some(chunk), Some(next)) = (self.chunks.get(&pos), self.chunks.get(&next_pos)) else {
+            return;
+        };
+
+        // dark chunks have nothing to give
+        if chunk.lighting().single() == Some([0, 0]) {
+            return;
+        }
+
+        let origin = chunk_origin(pos);
+
+        for (inner, outer) in face(offset) {
+            let levels  = chunk.light(inner[0], inner[1], inner[2]);
+            let block   = next.block(outer[0], outer[1], outer[2]);
+            let current = next.light(outer[0], outer[1], outer[2]);
+
+            for channel in Channel::ALL {
+                let queue = channel as usize;
+
+                if Self::brighter(self.registry, channel, offset, levels[queue], block, current[queue]).is_some() {
+                    self.spread[queue].push_back([0, 1, 2].map(|axis| origin[axis] + inner[axis] as i32));
+                }
+            }
+        }
+    }
+
+    // relight around changed block at world position
+    pub fn update_block(&mut self, pos: [i32; 3]) {
+        let Some((block, levels)) = self.cell(pos) else {
+            return;
+        };
+
+        // take away light the old block let through or emitted
+        for channel in Channel::ALL {
+            let level = levels[channel as usize];
+
+            if level > 0 {
+                self.set(pos, channel, 0);
+                self.darkened[channel as usize].push_back((pos, level));
+            }
+        }
+
+        let def = self.registry.get(block);
+
+        if def.emissive > 0 {
+            self.set(pos, Channel::Block, def.emissive);
+            self.spread[Channel::Block as usize].push_back(pos);
+        }
+
+        // light flows back in through transparent blocks
+        if def.transparent {
+            for offset in NEIGHBOURS {
+                let next = chunk::offset(pos, offset);
+
+                for channel in Channel::ALL {
+                    self.spread[channel as usize].push_back(next);
+                }
+            }
+
+            let above = chunk::offset(pos, UP);
+
+            if self.cell(above).is_none() && self.open_sky(above) {
+                self.set(pos, Channel::Sky, if block.is_air() { MAX_LIGHT } else { MAX_LIGHT - 1 });
+                self.spread[Channel::Sky as usize].push_back(pos);
+            }
+        }
+
+        self.propagate();
+    }
+
+    // remove darkened light, then spread light into the gaps
+    fn propagate(&mut self) {
+        for channel in Channel::ALL {
+            self.darken(channel);
+            self.brighten(channel);
+        }
+    }
+
+    // clear light fed by darkened positions
+    fn darken(&mut self, channel: Channel) {
+        while let Some((pos, level)) = self.darkened[channel as usize].pop_front() {
+            for offset in NEIGHBOURS {
+                let next = chunk::offset(pos, offset);
+
+                let Some((block, levels)) = self.cell(next) else {
+                    continue;
+                };
+
+                let next_level = levels[channel as usize];
+
+                if next_level == 0 {
+                    continue;
+                }
+
+                // unbroken sky columns keep full light going down
+                let column = channel == Channel::Sky && offset == DOWN && level == MAX_LIGHT && next_level == MAX_LIGHT;
+                let source = channel == Channel::Block && self.registry.get(block).emissive == next_level;
+
+                if (next_level < level || column) && !source {
+                    self.set(next, channel, 0);
+                    self.darkened[channel as usize].push_back((next, next_level));
+                } else {
+                    // brighter light from elsewhere fills the gap
+                    self.spread[channel as usize].push_back(next);
+                }
+            }
+        }
+    }
+
+    // flood light outwards, losing a level per block
+    fn brighten(&mut self, channel: Channel) {
+        let queue = channel as usize;
+
+        while let Some(pos) = self.spread[queue].pop_front() {
+            let (chunk_pos, local) = Self::locate(pos);
+
+            let Some(chunk) = self.chunks.get_mut(&chunk_pos) else {
+                continue;
+            };
+
+            let level = chunk.light(local[0], local[1], local[2])[queue];
+
+            if level <= 1 {
+                continue;
+            }
+
+            // neighbours in the same chunk skip looking it up again
+            let mut lit     = false;
+            let mut outside = Vec::new();
+
+            for offset in NEIGHBOURS {
+                let next = [0, 1, 2].map(|axis| local[axis] as i32 + offset[axis]);
+
+                if next.iter().any(|&coord| !(0..SIZE).contains(&coord)) {
+                    outside.push(offset);
+                    continue;
+                }
+
+                let [x, y, z] = next.map(|coord| coord as usize);
+                let current   = chunk.light(x, y, z)[queue];
+
+                if let Some(next_level) = Self::brighter(self.registry, channel, offset, level, chunk.block(x, y, z), current) {
+                    self.grown += chunk.set_light(x, y, z, channel, next_level);
+                    self.spread[queue].push_back(chunk::offset(pos, offset));
+
+                    Self::mark_border(&mut self.touched, chunk_pos, [x, y, z]);
+                    lit = true;
+                }
+            }
+
+            if lit {
+                self.touched.insert(chunk_pos);
+            }
+
+            for offset in outside {
+                let next = chunk::offset(pos, offset);
+
+                let Some((block, levels)) = self.cell(next) else {
+                    continue;
+                };
+
+                if let Some(next_level) = Self::brighter(self.registry, channel, offset, level, block, levels[queue]) {
+                    self.set(next, channel, next_level);
+                    self.spread[queue].push_back(next);
+                }
+            }
+        }
+    }
+
+    // light spreading from level into block by offset, none if no brighter
+    fn brighter(registry: &BlockRegistry, channel: Channel, offset: [i32; 3], level: u8, block: Block, current: u8) -> Option<u8> {
+        if level == 0 || !registry.get(block).transparent {
+            return None;
+        }
+
+        // full sky light falls through air without fading
+        let falling    = channel == Channel::Sky && offset == DOWN && level == MAX_LIGHT && block.is_air();
+        let next_level = if falling { MAX_LIGHT } else { level - 1 };
+
+        (next_level > current).then_some(next_level)
+    }
+}
+
+// local blocks on chunk face towards offset, paired with the blocks facing them in the neighbour
+fn face(offset: [i32; 3]) -> impl Iterator<Item = ([usize; 3], [usize; 3])> {
+    let axis = offset.iter().position(|&dir| dir != 0).unwrap();
+    let last = SIZE as usize - 1;
+
+    let (edge, facing) = if offset[axis] > 0 { (last, 0) } else { (0, last) };
+
+    itertools::iproduct!(0..SIZE as usize, 0..SIZE as usize).map(move |(u, v)| {
+        let mut inner = [0; 3];
+        inner[axis]           = edge;
+        inner[(axis + 1) % 3] = u;
+        inner[(axis + 2) % 3] = v;
+
+        let mut outer = inner;
+        outer[axis]   = facing;
+
+        (inner, outer)
+    })
+}
+
+// world position of first block of chunk
+fn chunk_origin(pos: ChunkPos) -> [i32; 3] {
+    [pos.0 * SIZE, pos.1 * SIZE, pos.2 * SIZE]
+}
+
+// neighbouring chunk position
+fn chunk_offset(pos: ChunkPos, offset: [i32; 3]) -> ChunkPos {
+    (pos.0 + offset[0], pos.1 + offset[1], pos.2 + offset[2])
+}
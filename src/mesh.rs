@@ -0,0 +1,473 @@
+use crate::block::{Block, BlockRegistry, FLUID_LEVELS};
+use crate::chunk::{Chunk, Neighbours, CHUNK_SIZE};
+use crate::vertex::INDICES;
+use crate::instance::Instance;
+
+use bytemuck::{Pod, Zeroable};
+
+use std::mem;
+use std::str::FromStr;
+
+// face corners and texture positions
+pub const CORNERS: [[([u8; 3], [f32; 2]); 4]; 6] = [
+    // front
+    [([0, 0, 1], [0.0, 1.0]), ([1, 0, 1], [1.0, 1.0]), ([0, 1, 1], [0.0, 0.0]), ([1, 1, 1], [1.0, 0.0])],
+
+    // back
+    [([0, 0, 0], [1.0, 1.0]), ([1, 0, 0], [0.0, 1.0]), ([0, 1, 0], [1.0, 0.0]), ([1, 1, 0], [0.0, 0.0])],
+
+    // left
+    [([0, 0, 0], [0.0, 1.0]), ([0, 0, 1], [1.0, 1.0]), ([0, 1, 0], [0.0, 0.0]), ([0, 1, 1], [1.0, 0.0])],
+
+    // right
+    [([1, 0, 1], [0.0, 1.0]), ([1, 0, 0], [1.0, 1.0]), ([1, 1, 1], [0.0, 0.0]), ([1, 1, 0], [1.0, 0.0])],
+
+    // top
+    [([0, 1, 1], [0.0, 1.0]), ([1, 1, 1], [1.0, 1.0]), ([0, 1, 0], [0.0, 0.0]), ([1, 1, 0], [1.0, 0.0])],
+
+    // bottom
+    [([0, 0, 1], [1.0, 1.0]), ([1, 0, 1], [0.0, 1.0]), ([0, 0, 0], [1.0, 0.0]), ([1, 0, 0], [0.0, 0.0])],
+];
+
+// face normal axis, direction and texture axes
+pub const AXES: [(usize, i32, usize, usize); 6] = [
+    (2,  1, 0, 1),
+    (2, -1, 0, 1),
+    (0, -1, 2, 1),
+    (0,  1, 2, 1),
+    (1,  1, 0, 2),
+    (1, -1, 0, 2),
+];
+
+// quad corners after turning it, moving its diagonal to the other pair
+const TURN: [u32; 4] = [1, 3, 0, 2];
+
+// corner brightness from 0 to 3 by the blocks beside and diagonal to it
+pub fn occlusion(side_a: bool, side_b: bool, corner: bool) -> u8 {
+    // two sides hide the corner block entirely
+    if side_a && side_b {
+        0
+    } else {
+        3 - side_a as u8 - side_b as u8 - corner as u8
+    }
+}
+
+// texture and shading of face
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Shade {
+    // texture tile index
+    tile: u16,
+
+    // packed sky and block light in front of face
+    light: u8,
+
+    // corner brightness from 0 to 3
+    ao: [u8; 4],
+}
+
+impl Shade {
+    // unshaded corners
+    const OPEN: [u8; 4] = [3; 4];
+}
+
+// chunk meshing backend
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Mesher {
+    // instance per block
+    #[default]
+    Instanced,
+
+    // merged quads
+    Greedy,
+}
+
+impl Mesher {
+    // other backend
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Instanced => Self::Greedy,
+            Self::Greedy    => Self::Instanced,
+        }
+    }
+}
+
+impl FromStr for Mesher {
+    type Err = String;
+
+    // parse backend name
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "instanced" => Ok(Self::Instanced),
+            "greedy"    => Ok(Self::Greedy),
+
+            _ => Err(format!("unknown mesher: {}", text)),
+        }
+    }
+}
+
+// opaque chunk geometry
+pub enum SolidMesh {
+    // block instances
+    Instanced(Vec<Instance>),
+
+    // merged quads
+    Greedy(Mesh),
+}
+
+// chunk geometry
+pub struct ChunkMesh {
+    // opaque and cut out blocks
+    pub solid: SolidMesh,
+
+    // blended blocks and fluid surfaces
+    pub translucent: Mesh,
+}
+
+// mesh vertex
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct MeshVertex {
+    // position
+    pos: [f32; 4],
+
+    // texture position in blocks
+    tex_pos: [f32; 2],
+
+    // texture tile index
+    tile: u32,
+
+    // face index
+    face: u32,
+
+    // packed sky and block light in front of face
+    light: u32,
+
+    // corner brightness from 0 to 3
+    ao: u32,
+}
+
+impl MeshVertex {
+    // layout attributes
+    const ATTRS: [wgpu::VertexAttribute; 6] =
+        wgpu::vertex_attr_array![0 => Float32x4, 1 => Float32x2, 2 => Uint32, 3 => Uint32, 4 => Uint32, 5 => Uint32];
+
+    // world position
+    pub fn pos(&self) -> [f32; 4] {
+        self.pos
+    }
+
+    // packed sky and block light
+    pub fn light(&self) -> u8 {
+        self.light as u8
+    }
+
+    // corner brightness
+    pub fn ao(&self) -> u8 {
+        self.ao as u8
+    }
+
+    // memory layout
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<MeshVertex>() as wgpu::BufferAddress,
+            step_mode:    wgpu::VertexStepMode::Vertex,
+            attributes:   &Self::ATTRS,
+        }
+    }
+}
+
+// chunk mesh
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    // vertices
+    pub vertices: Vec<MeshVertex>,
+
+    // indices
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    // create greedy mesh
+    pub fn greedy(chunk: &Chunk, neighbours: &Neighbours, registry: &BlockRegistry) -> Self {
+        let mut mesh = Self::default();
+
+        // nothing to draw
+        if chunk.is_empty() {
+            return mesh;
+        }
+
+        let size = [CHUNK_SIZE as usize; 3];
+
+        for (face, &(axis, dir, u_axis, v_axis)) in AXES.iter().enumerate() {
+            let (u_size, v_size) = (size[u_axis], size[v_axis]);
+
+            for layer in 0..size[axis] {
+                // visible faces in layer with their shading
+                let mut mask: Vec<Option<(Block, Shade)>> = vec![None; u_size * v_size];
+
+                for (u, v) in itertools::iproduct!(0..u_size, 0..v_size) {
+                    let mut pos = [0; 3];
+                    pos[axis]   = layer as i32;
+                    pos[u_axis] = u as i32;
+                    pos[v_axis] = v as i32;
+
+                    let block = chunk.block(pos[0] as usize, pos[1] as usize, pos[2] as usize);
+
+                    // facing block
+                    let mut next = pos;
+                    next[axis] += dir;
+
+                    // blended blocks are meshed separately
+                    let opaque = !block.is_air() && !registry.get(block).blended();
+
+                    if opaque && chunk.face_visible(block, next, neighbours, registry) {
+                        mask[v * u_size + u] = Some((block, Shade {
+                            tile:  registry.get(block).tile(face),
+                            light: chunk.light_at(next[0], next[1], next[2], neighbours),
+                            ao:    chunk.face_ao(pos, face, neighbours, registry),
+                        }));
+                    }
+                }
+
+                // merge faces into quads
+                for v in 0..v_size {
+                    let mut u = 0;
+
+                    while u < u_size {
+                        let Some(key) = mask[v * u_size + u] else {
+                            u += 1;
+                            continue;
+                        };
+
+                        // grow along u
+                        let width =
+                            (u..u_size)
+                                .take_while(|&u| mask[v * u_size + u] == Some(key))
+                                .count();
+
+                        // grow along v
+                        let height =
+                            (v..v_size)
+                                .take_while(|&v| {
+                                    (u..u + width).all(|u| mask[v * u_size + u] == Some(key))
+                                }).count();
+
+                        // clear merged faces
+                        for (v, u) in itertools::iproduct!(v..v + height, u..u + width) {
+                            mask[v * u_size + u] = None;
+                        }
+
+                        // quad bounds
+                        let mut min = [0; 3];
+                        let mut max = [0; 3];
+
+                        min[axis] = layer;
+                        max[axis] = layer + 1;
+
+                        min[u_axis] = u;
+                        max[u_axis] = u + width;
+
+                        min[v_axis] = v;
+                        max[v_axis] = v + height;
+
+                        mesh.quad(chunk, face, key.1, min, max, (u_axis, v_axis));
+                        u += width;
+                    }
+                }
+            }
+        }
+
+        mesh
+    }
+
+    // create mesh of blended blocks, fluid surfaces lowered by level
+    pub fn translucent(chunk: &Chunk, neighbours: &Neighbours, registry: &BlockRegistry) -> Self {
+        let mut mesh = Self::default();
+
+        // nothing to draw
+        if !chunk.blocks().palette().iter().any(|&block| registry.get(block).blended()) {
+            return mesh;
+        }
+
+        let size = CHUNK_SIZE as i32;
+
+        for (x, y, z) in itertools::iproduct!(0..size, 0..size, 0..size) {
+            let block = chunk.block(x as usize, y as usize, z as usize);
+            let def   = registry.get(block);
+
+            if !def.blended() {
+                continue;
+            }
+
+            // other blocks fill the whole cube
+            let fluid  = registry.fluid(block);
+            let height = fluid.map_or(Some(1.0), |fluid| {
+                Self::fluid_height(chunk, [x, y, z], fluid.source, neighbours, registry)
+            }).unwrap_or(1.0);
+
+            for (face, &(axis, dir, _, _)) in AXES.iter().enumerate() {
+                let mut next = [x, y, z];
+                next[axis] += dir;
+
+                let next_block = chunk.block_at(next[0], next[1], next[2], neighbours);
+
+                // faces against blocks take their own light
+                let lit   = if registry.get(next_block).transparent { next } else { [x, y, z] };
+                let light = chunk.light_at(lit[0], lit[1], lit[2], neighbours);
+
+                let shade = Shade { tile: def.tile(face), light, ao: Shade::OPEN };
+
+                let Some(fluid) = fluid else {
+                    if chunk.face_visible(block, next, neighbours, registry) {
+                        mesh.face(chunk, face, shade, [x, y, z], (0.0, 1.0));
+                    }
+
+                    continue;
+                };
+
+                // same fluid hides the face up to its own surface
+                let bottom = match Self::fluid_height(chunk, next, fluid.source, neighbours, registry) {
+                    Some(_) if axis == 1 => continue,
+                    Some(next_height)    => next_height,
+
+                    // lowered surfaces show under blocks
+                    None if face == 4 && height < 1.0             => 0.0,
+                    None if !registry.get(next_block).transparent => continue,
+                    None                                          => 0.0,
+                };
+
+                if bottom < height {
+                    mesh.face(chunk, face, shade, [x, y, z], (bottom, height));
+                }
+            }
+        }
+
+        mesh
+    }
+
+    // order quads back to front from eye, keeping vertices in place
+    pub fn sort_back_to_front(&mut self, eye: [f32; 3]) {
+        // squared distance from eye to center of quad starting at vertex
+        let distance = |base: u32| {
+            let center = (base..base + 4).fold([0.0; 3], |sum: [f32; 3], idx| {
+                let pos = self.vertices[idx as usize].pos;
+                [sum[0] + pos[0], sum[1] + pos[1], sum[2] + pos[2]]
+            });
+
+            (0..3).map(|axis| (center[axis] / 4.0 - eye[axis]).powi(2)).sum::<f32>()
+        };
+
+        // quads own four vertices each, their indices in runs of six
+        let mut quads =
+            self.indices
+                .chunks_exact(6)
+                .map(|quad| (distance(quad.iter().copied().min().unwrap_or(0)), quad))
+                .collect::<Vec<_>>();
+
+        quads.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        self.indices = quads.into_iter().flat_map(|(_, quad)| quad.iter().copied()).collect();
+    }
+
+    // surface height of fluid at local position, full below the same fluid
+    fn fluid_height(
+        chunk:      &Chunk,
+        pos:        [i32; 3],
+        source:     Block,
+        neighbours: &Neighbours,
+        registry:   &BlockRegistry,
+    ) -> Option<f32> {
+        let [x, y, z] = pos;
+
+        let same = |block| registry.fluid(block).is_some_and(|fluid| fluid.source == source);
+        let fluid = registry.fluid(chunk.block_at(x, y, z, neighbours)).filter(|fluid| fluid.source == source)?;
+
+        if same(chunk.block_at(x, y + 1, z, neighbours)) {
+            Some(1.0)
+        } else {
+            Some(fluid.level as f32 / (FLUID_LEVELS + 1) as f32)
+        }
+    }
+
+    // add single block face spanning heights
+    fn face(&mut self, chunk: &Chunk, face: usize, shade: Shade, pos: [i32; 3], (bottom, top): (f32, f32)) {
+        let origin = chunk.origin();
+        let base   = self.vertices.len() as u32;
+
+        for (idx, (corner, tex_pos)) in CORNERS[face].into_iter().enumerate() {
+            let y = if corner[1] == 0 { bottom } else { top };
+
+            // side textures are cut rather than squashed
+            let tex_y = if face < 4 { 1.0 - if tex_pos[1] == 0.0 { top } else { bottom } } else { tex_pos[1] };
+
+            self.vertices.push(MeshVertex {
+                tile:    shade.tile as u32,
+                pos:     [
+                    origin[0] + (pos[0] + corner[0] as i32) as f32,
+                    origin[1] + pos[1] as f32 + y,
+                    origin[2] + (pos[2] + corner[2] as i32) as f32,
+                    1.0,
+                ],
+                tex_pos: [tex_pos[0], tex_y],
+                face:    face as u32,
+                light:   shade.light as u32,
+                ao:      shade.ao[idx] as u32,
+            });
+        }
+
+        self.triangulate(face, base, shade.ao);
+    }
+
+    // add quad
+    fn quad(
+        &mut self,
+        chunk: &Chunk,
+        face:  usize,
+        shade: Shade,
+        min:   [usize; 3],
+        max:   [usize; 3],
+        axes:  (usize, usize),
+    ) {
+        let origin = chunk.origin();
+        let base   = self.vertices.len() as u32;
+
+        for (idx, (corner, tex_pos)) in CORNERS[face].into_iter().enumerate() {
+            let pos = [0, 1, 2].map(|axis| {
+                let edge = if corner[axis] == 0 { min[axis] } else { max[axis] };
+                origin[axis] + edge as f32
+            });
+
+            self.vertices.push(MeshVertex {
+                tile:    shade.tile as u32,
+                pos:     [pos[0], pos[1], pos[2], 1.0],
+                tex_pos: [
+                    tex_pos[0] * (max[axes.0] - min[axes.0]) as f32,
+                    tex_pos[1] * (max[axes.1] - min[axes.1]) as f32,
+                ],
+                face:  face as u32,
+                light: shade.light as u32,
+                ao:    shade.ao[idx] as u32,
+            });
+        }
+
+        self.triangulate(face, base, shade.ao);
+    }
+
+    // add quad triangles, split along the brighter diagonal so corners shade evenly
+    fn triangulate(&mut self, face: usize, base: u32, ao: [u8; 4]) {
+        let turn = ao[0] + ao[3] > ao[1] + ao[2];
+
+        // reuse cube face winding
+        self.indices.extend(
+            INDICES[face * 6..face * 6 + 6]
+                .iter()
+                .map(|&idx| idx as u32 - face as u32 * 4)
+                .map(|corner| base + if turn { TURN[corner as usize] } else { corner })
+        );
+    }
+
+    // number of quads
+    pub fn quads(&self) -> usize {
+        self.vertices.len() / 4
+    }
+}
@@ -0,0 +1,170 @@
+use crate::seed::WorldSeed;
+use crate::mesh::Mesher;
+
+// launch options
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    // world seed, random when unset
+    pub seed: Option<WorldSeed>,
+
+    // chunk mesher
+    pub mesher: Mesher,
+
+    // maximum loaded chunks
+    pub max_chunks: Option<usize>,
+
+    // maximum chunk memory in mebibytes
+    pub max_memory: Option<usize>,
+
+    // world bottom and top heights
+    pub min_y: Option<i32>,
+    pub max_y: Option<i32>,
+
+    // cave threshold, tunnel width and overhang depth
+    pub cave_threshold: Option<f64>,
+    pub tunnel_width:   Option<f64>,
+    pub overhangs:      Option<f64>,
+
+    // starting time of day in hours and real seconds per day
+    pub time:       Option<f32>,
+    pub day_length: Option<f32>,
+
+    // block definitions file
+    pub blocks: Option<String>,
+
+    // world save directory or browser world name
+    pub world: Option<String>,
+
+    // exported world file to unpack
+    pub import: Option<String>,
+}
+
+impl Options {
+    // parse from key value pairs
+    pub fn from_pairs<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut options = Self::default();
+
+        for (key, value) in pairs {
+            match key {
+                // flags given without value
+                "seed" | "blocks" | "world" | "import" if value.is_empty() => {
+                    log::warn!("missing value for option: {}", key);
+                }
+
+                "seed" => {
+                    let Ok(seed) = value.parse::<WorldSeed>();
+                    options.seed = Some(seed);
+                }
+
+                "mesher" => match value.parse() {
+                    Ok(mesher) => options.mesher = mesher,
+                    Err(err)   => log::warn!("{}", err),
+                },
+
+                "max-chunks" => match value.parse() {
+                    Ok(max) => options.max_chunks = Some(max),
+                    Err(_)  => log::warn!("invalid chunk limit: {}", value),
+                },
+
+                "max-memory" => match value.parse() {
+                    Ok(max) => options.max_memory = Some(max),
+                    Err(_)  => log::warn!("invalid memory limit: {}", value),
+                },
+
+                "min-y" => match value.parse() {
+                    Ok(y)  => options.min_y = Some(y),
+                    Err(_) => log::warn!("invalid world bottom: {}", value),
+                },
+
+                "max-y" => match value.parse() {
+                    Ok(y)  => options.max_y = Some(y),
+                    Err(_) => log::warn!("invalid world top: {}", value),
+                },
+
+                "cave-threshold" => match value.parse() {
+                    Ok(threshold) => options.cave_threshold = Some(threshold),
+                    Err(_)        => log::warn!("invalid cave threshold: {}", value),
+                },
+
+                "tunnel-width" => match value.parse() {
+                    Ok(width) => options.tunnel_width = Some(width),
+                    Err(_)    => log::warn!("invalid tunnel width: {}", value),
+                },
+
+                "overhangs" => match value.parse() {
+                    Ok(depth) => options.overhangs = Some(depth),
+                    Err(_)    => log::warn!("invalid overhang depth: {}", value),
+                },
+
+                "time" => match value.parse() {
+                    Ok(hours) => options.time = Some(hours),
+                    Err(_)    => log::warn!("invalid time of day: {}", value),
+                },
+
+                "day-length" => match value.parse() {
+                    Ok(seconds) => options.day_length = Some(seconds),
+                    Err(_)      => log::warn!("invalid day length: {}", value),
+                },
+
+                "blocks" => {
+                    options.blocks = Some(value.to_string());
+                }
+
+                "world" => {
+                    options.world = Some(value.to_string());
+                }
+
+                "import" => {
+                    options.import = Some(value.to_string());
+                }
+
+                _ => log::warn!("invalid option: {}", key),
+            }
+        }
+
+        options
+    }
+
+    // parse from command line
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn parse() -> Self {
+        let args = std::env::args().skip(1).collect::<Vec<_>>();
+        let mut pairs = Vec::new();
+
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            let Some(flag) = arg.strip_prefix("--") else {
+                log::warn!("unexpected argument: {}", arg);
+                continue;
+            };
+
+            // --key=value or --key value
+            match flag.split_once('=') {
+                Some((key, value)) => pairs.push((key, value)),
+                None => pairs.push((flag, iter.next().map(String::as_str).unwrap_or(""))),
+            }
+        }
+
+        Self::from_pairs(pairs)
+    }
+
+    // parse from url query
+    #[cfg(target_arch = "wasm32")]
+    pub fn parse() -> Self {
+        let search =
+            web_sys::window()
+                .and_then(|win| win.location().search().ok())
+                .unwrap_or_default();
+
+        let pairs =
+            search
+                .trim_start_matches('?')
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+                .collect::<Vec<_>>();
+
+        Self::from_pairs(pairs)
+    }
+}
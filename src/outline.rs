@@ -0,0 +1,109 @@
+use bytemuck::{Pod, Zeroable};
+
+use std::mem;
+
+// outline growth past block faces
+const INFLATE: f32 = 0.002;
+
+// box edges as corner pairs
+const EDGES: [([u8; 3], [u8; 3]); 12] = [
+    // bottom
+    ([0, 0, 0], [1, 0, 0]),
+    ([1, 0, 0], [1, 0, 1]),
+    ([1, 0, 1], [0, 0, 1]),
+    ([0, 0, 1], [0, 0, 0]),
+
+    // top
+    ([0, 1, 0], [1, 1, 0]),
+    ([1, 1, 0], [1, 1, 1]),
+    ([1, 1, 1], [0, 1, 1]),
+    ([0, 1, 1], [0, 1, 0]),
+
+    // sides
+    ([0, 0, 0], [0, 1, 0]),
+    ([1, 0, 0], [1, 1, 0]),
+    ([1, 0, 1], [1, 1, 1]),
+    ([0, 0, 1], [0, 1, 1]),
+];
+
+// number of line vertices
+pub const OUTLINE_VERTICES: u32 = EDGES.len() as u32 * 2;
+
+// outline vertex
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct OutlineVertex {
+    // position
+    pos: [f32; 4],
+}
+
+impl OutlineVertex {
+    // layout attributes
+    const ATTRS: [wgpu::VertexAttribute; 1] =
+        wgpu::vertex_attr_array![0 => Float32x4];
+
+    // memory layout
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<OutlineVertex>() as wgpu::BufferAddress,
+            step_mode:    wgpu::VertexStepMode::Vertex,
+            attributes:   &Self::ATTRS,
+        }
+    }
+}
+
+// line vertices around block
+pub fn outline_vertices(pos: [i32; 3]) -> Vec<OutlineVertex> {
+    EDGES
+        .iter()
+        .flat_map(|&(start, end)| [start, end])
+        .map(|corner| {
+            let pos = [0, 1, 2].map(|axis| {
+                let edge = if corner[axis] == 0 { -INFLATE } else { 1.0 + INFLATE };
+                pos[axis] as f32 + edge
+            });
+
+            OutlineVertex { pos: [pos[0], pos[1], pos[2], 1.0] }
+        }).collect()
+}
+
+// selected block outline
+pub struct Outline {
+    // line vertices
+    pub buf: wgpu::Buffer,
+
+    // outlined block
+    target: Option<[i32; 3]>,
+}
+
+impl Outline {
+    // create outline
+    pub fn new(device: &wgpu::Device) -> Self {
+        let buf = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("outline_buf"),
+            size:  (OUTLINE_VERTICES as usize * mem::size_of::<OutlineVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self { buf, target: None }
+    }
+
+    // outlined block
+    pub fn target(&self) -> Option<[i32; 3]> {
+        self.target
+    }
+
+    // move outline to block
+    pub fn set_target(&mut self, target: Option<[i32; 3]>, queue: &wgpu::Queue) {
+        if target == self.target {
+            return;
+        }
+
+        if let Some(pos) = target {
+            queue.write_buffer(&self.buf, 0, bytemuck::cast_slice(&outline_vertices(pos)));
+        }
+
+        self.target = target;
+    }
+}
@@ -0,0 +1,183 @@
+use crate::aabb::Aabb;
+use crate::world::World;
+
+use cgmath::{Point3, Vector3, InnerSpace, Zero};
+
+// player width
+pub const PLAYER_WIDTH: f32 = 0.6;
+
+// player height
+pub const PLAYER_HEIGHT: f32 = 1.8;
+
+// camera height above feet
+pub const PLAYER_EYE: f32 = 1.62;
+
+// highest ledge climbed without jumping
+pub const STEP_HEIGHT: f32 = 1.0;
+
+// movement speeds
+const WALK_SPEED:   f32 = 4.3;
+const SPRINT_SPEED: f32 = 5.6;
+
+// vertical acceleration and limits
+const GRAVITY:        f32 = 32.0;
+const JUMP_SPEED:     f32 = 9.0;
+const TERMINAL_SPEED: f32 = 60.0;
+
+// longest simulated step
+const MAX_DT: f32 = 0.05;
+
+// walking player
+pub struct Player {
+    // feet position
+    pos: Point3<f32>,
+
+    // velocity
+    velocity: Vector3<f32>,
+
+    // standing on block
+    on_ground: bool,
+}
+
+impl Player {
+    // create player
+    pub fn new(pos: Point3<f32>) -> Self {
+        Self {
+            pos,
+            velocity:  Vector3::zero(),
+            on_ground: false,
+        }
+    }
+
+    // feet position
+    pub fn pos(&self) -> Point3<f32> {
+        self.pos
+    }
+
+    // velocity
+    pub fn velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
+    // check if standing on block
+    pub fn on_ground(&self) -> bool {
+        self.on_ground
+    }
+
+    // camera position
+    pub fn eye(&self) -> Point3<f32> {
+        self.pos + Vector3::unit_y() * PLAYER_EYE
+    }
+
+    // move to camera position
+    pub fn set_eye(&mut self, eye: Point3<f32>) {
+        self.pos       = eye - Vector3::unit_y() * PLAYER_EYE;
+        self.velocity  = Vector3::zero();
+        self.on_ground = false;
+    }
+
+    // bounding box
+    pub fn bounds(&self) -> Aabb {
+        let half = PLAYER_WIDTH / 2.0;
+
+        Aabb::new(
+            Point3::new(self.pos.x - half, self.pos.y, self.pos.z - half),
+            Point3::new(self.pos.x + half, self.pos.y + PLAYER_HEIGHT, self.pos.z + half),
+        )
+    }
+
+    // walk in direction with gravity
+    pub fn walk(&mut self, world: &World, direction: Vector3<f32>, jump: bool, sprint: bool, dt: f32) {
+        let dt = dt.min(MAX_DT);
+
+        // hold still until ground loads
+        let [x, y, z] = [self.pos.x, self.pos.y, self.pos.z].map(|v| v.floor() as i32);
+
+        if !world.block_loaded(x, y, z) || !world.block_loaded(x, y - 1, z) {
+            return;
+        }
+
+        // horizontal movement
+        let direction = Vector3::new(direction.x, 0.0, direction.z);
+        let speed     = if sprint { SPRINT_SPEED } else { WALK_SPEED };
+
+        let horizontal = if direction.magnitude2() > 0.0 {
+            direction.normalize() * speed
+        } else {
+            Vector3::zero()
+        };
+
+        self.velocity.x = horizontal.x;
+        self.velocity.z = horizontal.z;
+
+        // vertical movement
+        if jump && self.on_ground {
+            self.velocity.y = JUMP_SPEED;
+        }
+
+        self.velocity.y = (self.velocity.y - GRAVITY * dt).max(-TERMINAL_SPEED);
+
+        let motion    = self.velocity * dt;
+        let bounds    = self.bounds();
+        let mut moved = Self::collide(world, &bounds, motion);
+
+        let blocked = moved.x != motion.x || moved.z != motion.z;
+
+        // climb onto ledge
+        if self.on_ground && blocked {
+            let up     = Self::collide(world, &bounds, Vector3::unit_y() * STEP_HEIGHT);
+            let raised = bounds.translate(up);
+
+            let side    = Self::collide(world, &raised, Vector3::new(motion.x, 0.0, motion.z));
+            let lowered = raised.translate(side);
+
+            let down    = Self::collide(world, &lowered, -up);
+            let stepped = up + side + down;
+
+            if stepped.x.abs() + stepped.z.abs() > moved.x.abs() + moved.z.abs() {
+                moved = stepped;
+            }
+        }
+
+        // land or hit ceiling
+        self.on_ground = motion.y < 0.0 && moved.y > motion.y;
+
+        if moved.y != motion.y {
+            self.velocity.y = 0.0;
+        }
+
+        self.pos += moved;
+    }
+
+    // limit motion by solid blocks
+    fn collide(world: &World, bounds: &Aabb, motion: Vector3<f32>) -> Vector3<f32> {
+        let region = bounds.union(&bounds.translate(motion));
+
+        // solid blocks in reach
+        let blocks =
+            itertools::iproduct!(
+                region.min.x.floor() as i32..region.max.x.ceil() as i32,
+                region.min.y.floor() as i32..region.max.y.ceil() as i32,
+                region.min.z.floor() as i32..region.max.z.ceil() as i32
+            )
+                .filter(|&(x, y, z)| world.is_solid(x, y, z))
+                .map(|(x, y, z)| Aabb::block([x, y, z]))
+                .collect::<Vec<_>>();
+
+        let mut bounds = *bounds;
+        let mut moved  = Vector3::zero();
+
+        // resolve vertical first
+        for axis in [1, 0, 2] {
+            let dist = blocks.iter().fold(motion[axis], |dist, block| bounds.clip(block, axis, dist));
+
+            let mut offset = Vector3::zero();
+            offset[axis]   = dist;
+
+            bounds      = bounds.translate(offset);
+            moved[axis] = dist;
+        }
+
+        moved
+    }
+}
@@ -0,0 +1,89 @@
+use cgmath::{Point3, Vector3, InnerSpace};
+
+// ray hit
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RayHit {
+    // hit block position
+    pub pos: [i32; 3],
+
+    // normal of entered face
+    pub normal: [i32; 3],
+
+    // distance along ray
+    pub distance: f32,
+}
+
+impl RayHit {
+    // position of block on hit face
+    pub fn adjacent(&self) -> [i32; 3] {
+        [0, 1, 2].map(|axis| self.pos[axis] + self.normal[axis])
+    }
+}
+
+// walk blocks along ray until hit
+pub fn raycast(
+    origin:       Point3<f32>,
+    direction:    Vector3<f32>,
+    max_distance: f32,
+    mut hit:      impl FnMut([i32; 3]) -> bool,
+) -> Option<RayHit> {
+    let length = direction.magnitude2();
+
+    // no direction to walk
+    if length == 0.0 || !length.is_finite() {
+        return None;
+    }
+
+    let origin    = [origin.x, origin.y, origin.z];
+    let direction = direction.normalize();
+    let direction = [direction.x, direction.y, direction.z];
+
+    let mut pos = origin.map(|coord| coord.floor() as i32);
+
+    // starting inside block
+    if hit(pos) {
+        return Some(RayHit { pos, normal: [0; 3], distance: 0.0 });
+    }
+
+    let step = direction.map(|dir| dir.signum() as i32);
+
+    // distance between block borders
+    let delta = direction.map(|dir| (1.0 / dir).abs());
+
+    // distance to next border
+    let mut next = [0, 1, 2].map(|axis| {
+        let dir = direction[axis];
+
+        if dir > 0.0 {
+            (pos[axis] as f32 + 1.0 - origin[axis]) / dir
+        } else if dir < 0.0 {
+            (origin[axis] - pos[axis] as f32) / -dir
+        } else {
+            f32::INFINITY
+        }
+    });
+
+    loop {
+        // cross nearest border
+        let axis =
+            (0..3)
+                .min_by(|&a, &b| next[a].total_cmp(&next[b]))
+                .unwrap();
+
+        let distance = next[axis];
+
+        if distance > max_distance {
+            return None;
+        }
+
+        pos[axis]  += step[axis];
+        next[axis] += delta[axis];
+
+        if hit(pos) {
+            let mut normal = [0; 3];
+            normal[axis]   = -step[axis];
+
+            return Some(RayHit { pos, normal, distance });
+        }
+    }
+}
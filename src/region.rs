@@ -0,0 +1,331 @@
+use crate::seed::WorldSeed;
+use crate::chunk::ChunkPos;
+use crate::storage::{ChunkStorage, SavedChunk};
+
+use anyhow::{Result, Context, bail, ensure};
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+// region size in chunks along every axis
+pub const REGION_SIZE: i32 = 16;
+
+// region file format version
+pub const REGION_VERSION: u16 = 1;
+
+// region file signature
+const MAGIC: &[u8; 4] = b"BLKR";
+
+// chunks per region
+const REGION_VOLUME: usize = (REGION_SIZE * REGION_SIZE * REGION_SIZE) as usize;
+
+// offset table position after signature and version
+const TABLE_START: usize = MAGIC.len() + 2;
+
+// header length before chunk data
+const HEADER_LEN: usize = TABLE_START + REGION_VOLUME * 8;
+
+// chunk offset and length in region file, zero length for empty slots
+type Slot = (u32, u32);
+
+// chunks grouped into region files
+pub struct RegionStorage {
+    // world directory
+    dir: PathBuf,
+
+    // serialize access per region file
+    locks: Mutex<HashMap<ChunkPos, Arc<Mutex<()>>>>,
+}
+
+impl RegionStorage {
+    // open world directory
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+
+        fs::create_dir_all(dir.join("region"))
+            .with_context(|| format!("failed to create world: {}", dir.display()))?;
+
+        Ok(Self { dir, locks: Mutex::new(HashMap::new()) })
+    }
+
+    // region and slot of chunk
+    pub fn region(pos: ChunkPos) -> (ChunkPos, usize) {
+        let (x, y, z) = pos;
+
+        let region = (x.div_euclid(REGION_SIZE), y.div_euclid(REGION_SIZE), z.div_euclid(REGION_SIZE));
+        let slot   =
+            (y.rem_euclid(REGION_SIZE) * REGION_SIZE + z.rem_euclid(REGION_SIZE)) * REGION_SIZE +
+                x.rem_euclid(REGION_SIZE);
+
+        (region, slot as usize)
+    }
+
+    // chunk in region slot
+    fn slot_pos(region: ChunkPos, slot: usize) -> ChunkPos {
+        let slot = slot as i32;
+
+        (
+            region.0 * REGION_SIZE + slot % REGION_SIZE,
+            region.1 * REGION_SIZE + slot / (REGION_SIZE * REGION_SIZE),
+            region.2 * REGION_SIZE + slot / REGION_SIZE % REGION_SIZE,
+        )
+    }
+
+    // region file path
+    fn path(&self, region: ChunkPos) -> PathBuf {
+        self.dir.join("region").join(format!("r.{}.{}.{}.bin", region.0, region.1, region.2))
+    }
+
+    // run with region file locked, forgetting locks no one else holds
+    fn locked<T>(&self, region: ChunkPos, run: impl FnOnce() -> T) -> T {
+        let lock = self.locks.lock().unwrap().entry(region).or_default().clone();
+
+        let result = {
+            let _lock = lock.lock().unwrap();
+            run()
+        };
+
+        // held only by map and this call
+        let mut locks = self.locks.lock().unwrap();
+
+        if Arc::strong_count(&lock) == 2 {
+            locks.remove(&region);
+        }
+
+        result
+    }
+
+    // open existing region file
+    fn open_region(&self, region: ChunkPos) -> Result<Option<File>> {
+        match File::open(self.path(region)) {
+            Ok(file) => Ok(Some(file)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    // read offset table from header
+    fn read_header(file: &mut File) -> Result<Vec<Slot>> {
+        let mut header = Vec::with_capacity(HEADER_LEN);
+
+        file.seek(SeekFrom::Start(0))?;
+        file.take(HEADER_LEN as u64).read_to_end(&mut header)?;
+
+        ensure!(header.len() == HEADER_LEN && &header[..4] == MAGIC, "missing region header");
+
+        let version = u16::from_le_bytes([header[4], header[5]]);
+
+        if version != REGION_VERSION {
+            bail!("unsupported region version: {}", version);
+        }
+
+        Ok(header[TABLE_START..].chunks_exact(8).map(|entry| {
+            let offset = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+            let len    = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+
+            (offset, len)
+        }).collect())
+    }
+
+    // read chunk data of one slot
+    fn read_slot(file: &mut File, (offset, len): Slot) -> Result<Option<Vec<u8>>> {
+        // empty slot
+        if len == 0 {
+            return Ok(None);
+        }
+
+        let mut chunk = vec![0; len as usize];
+
+        file.seek(SeekFrom::Start(offset as u64))?;
+        file.read_exact(&mut chunk).context("chunk outside region")?;
+
+        Ok(Some(chunk))
+    }
+
+    // read every chunk in region file
+    fn read_region(&self, region: ChunkPos) -> Result<Vec<Option<Vec<u8>>>> {
+        self.locked(region, || {
+            let Some(mut file) = self.open_region(region)? else {
+                return Ok(vec![None; REGION_VOLUME]);
+            };
+
+            Self::read_header(&mut file)
+                .and_then(|slots| slots.into_iter().map(|slot| Self::read_slot(&mut file, slot)).collect())
+                .with_context(|| format!("invalid region: {}", self.path(region).display()))
+        })
+    }
+
+    // append chunks to region file and point their slots at them
+    fn write_region(&self, region: ChunkPos, chunks: &[(usize, &[u8])]) -> Result<()> {
+        self.locked(region, || self.write_locked(region, chunks))
+    }
+
+    // append chunks with region file locked
+    fn write_locked(&self, region: ChunkPos, chunks: &[(usize, &[u8])]) -> Result<()> {
+        let path = self.path(region);
+
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&path)?;
+        let len      = file.metadata()?.len();
+
+        // new region files start with empty slots
+        let mut slots = match len {
+            0 => vec![(0, 0); REGION_VOLUME],
+            _ => Self::read_header(&mut file).with_context(|| format!("invalid region: {}", path.display()))?,
+        };
+
+        let mut end = len.max(HEADER_LEN as u64);
+
+        // old data stays readable until header points past it
+        file.seek(SeekFrom::Start(end))?;
+
+        for &(slot, data) in chunks {
+            file.write_all(data)?;
+
+            slots[slot] = (end.try_into().context("region file too large")?, data.len() as u32);
+            end        += data.len() as u64;
+        }
+
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&Self::header(&slots))?;
+
+        // compact once replaced chunks take twice the space of live ones
+        let live = slots.iter().map(|&(_, len)| len as u64).sum::<u64>();
+
+        if end - HEADER_LEN as u64 > 2 * live {
+            let chunks = slots
+                .into_iter()
+                .map(|slot| Self::read_slot(&mut file, slot))
+                .collect::<Result<Vec<_>>>()?;
+
+            drop(file);
+
+            // replace file once fully written
+            let temp = path.with_extension("tmp");
+
+            fs::write(&temp, Self::serialize(&chunks))?;
+            fs::rename(&temp, &path)?;
+        }
+
+        Ok(())
+    }
+
+    // signature, version and offset table
+    fn header(slots: &[Slot]) -> Vec<u8> {
+        let mut header = Vec::with_capacity(HEADER_LEN);
+
+        header.extend(MAGIC);
+        header.extend(REGION_VERSION.to_le_bytes());
+
+        for (offset, len) in slots {
+            header.extend(offset.to_le_bytes());
+            header.extend(len.to_le_bytes());
+        }
+
+        header
+    }
+
+    // join chunks into compact region file
+    fn serialize(chunks: &[Option<Vec<u8>>]) -> Vec<u8> {
+        let mut slots = Vec::with_capacity(REGION_VOLUME);
+        let mut body  = Vec::<u8>::new();
+
+        for chunk in chunks {
+            slots.push(match chunk {
+                Some(chunk) => ((HEADER_LEN + body.len()) as u32, chunk.len() as u32),
+                None        => (0, 0),
+            });
+
+            body.extend(chunk.iter().flatten());
+        }
+
+        let mut data = Self::header(&slots);
+        data.extend(body);
+        data
+    }
+}
+
+impl ChunkStorage for RegionStorage {
+    // read chunk from its region slot
+    fn read(&self, pos: ChunkPos) -> Result<Option<Vec<u8>>> {
+        let (region, slot) = Self::region(pos);
+
+        self.locked(region, || {
+            let Some(mut file) = self.open_region(region)? else {
+                return Ok(None);
+            };
+
+            Self::read_header(&mut file)
+                .and_then(|slots| Self::read_slot(&mut file, slots[slot]))
+                .with_context(|| format!("invalid region: {}", self.path(region).display()))
+        })
+    }
+
+    // write chunk into its region
+    fn write(&self, pos: ChunkPos, data: &[u8]) -> Result<()> {
+        let (region, slot) = Self::region(pos);
+        self.write_region(region, &[(slot, data)])
+    }
+
+    // write chunks once per region
+    fn write_chunks(&self, chunks: &[SavedChunk]) -> Result<()> {
+        let mut regions = HashMap::<ChunkPos, Vec<(usize, &[u8])>>::new();
+
+        for (pos, data) in chunks {
+            let (region, slot) = Self::region(*pos);
+            regions.entry(region).or_default().push((slot, data));
+        }
+
+        regions.into_iter().try_for_each(|(region, chunks)| self.write_region(region, &chunks))
+    }
+
+    // read seed file
+    fn read_seed(&self) -> Result<Option<WorldSeed>> {
+        match fs::read_to_string(self.dir.join("seed.txt")) {
+            Ok(text) => Ok(Some(WorldSeed::new(text.trim().parse().context("invalid saved seed")?))),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    // write seed file
+    fn write_seed(&self, seed: WorldSeed) -> Result<()> {
+        Ok(fs::write(self.dir.join("seed.txt"), seed.to_string())?)
+    }
+
+    // read every region file
+    fn entries(&self) -> Result<Vec<SavedChunk>> {
+        let mut entries = Vec::new();
+
+        for file in fs::read_dir(self.dir.join("region"))? {
+            let name = file?.file_name();
+
+            // r.<x>.<y>.<z>.bin
+            let region = name
+                .to_str()
+                .and_then(|name| name.strip_prefix("r.")?.strip_suffix(".bin"))
+                .and_then(|name| {
+                    let mut coords = name.split('.').map(|coord| coord.parse::<i32>().ok());
+                    let region     = (coords.next()??, coords.next()??, coords.next()??);
+
+                    coords.next().is_none().then_some(region)
+                });
+
+            let Some(region) = region else {
+                continue;
+            };
+
+            for (slot, chunk) in self.read_region(region)?.into_iter().enumerate() {
+                let Some(chunk) = chunk else {
+                    continue;
+                };
+
+                entries.push((Self::slot_pos(region, slot), chunk));
+            }
+        }
+
+        Ok(entries)
+    }
+}
@@ -1,4 +1,6 @@
 use crate::state::State;
+use crate::options::Options;
+use crate::storage;
 
 use winit::event::{Event, WindowEvent, DeviceEvent};
 use winit::event_loop::EventLoop;
@@ -29,6 +31,12 @@ pub async fn result_runner() -> Result<()> {
         }
     }
 
+    // parse options
+    let options = Options::parse();
+
+    // open saved world
+    let storage = storage::open(&options).await?;
+
     // create event loop
     let event_loop = EventLoop::new()?;
 
@@ -69,15 +77,45 @@ pub async fn result_runner() -> Result<()> {
 
     let _ = event_loop.run(move |event, elwt| {
         if let Some(state) = state.as_mut() {
+            #[cfg(target_arch = "wasm32")]
+            if let Event::WindowEvent { event: WindowEvent::MouseInput { .. }, .. } = event {
+                let locked =
+                    web_sys::window()
+                        .and_then(|win| win.document())
+                        .is_some_and(|doc| doc.pointer_lock_element().is_some());
+
+                // click only grabs cursor while unlocked
+                if !locked {
+                    // hide and lock cursor
+                    window.set_cursor_grab(winit::window::CursorGrabMode::Locked).unwrap();
+                    window.set_cursor_visible(false);
+
+                    return;
+                }
+            }
+
             match event {
+                // state handles events first, kept out of a match guard
+                #[allow(clippy::collapsible_match)]
                 Event::WindowEvent { ref event, .. } => {
                     if !state.event(event) {
                         match event {
                             // quit when requested
                             WindowEvent::CloseRequested => {
+                                if let Err(err) = state.save() {
+                                    log::error!("failed to save world: {:#}", err);
+                                }
+
                                 elwt.exit();
                             }
 
+                            // save once hidden, browsers close tabs without requesting it
+                            WindowEvent::Occluded(true) => {
+                                if let Err(err) = state.save() {
+                                    log::error!("failed to save world: {:#}", err);
+                                }
+                            }
+
                             // window resized
                             WindowEvent::Resized(physical_size) => {
                                 state.resize(*physical_size);
@@ -102,7 +140,7 @@ pub async fn result_runner() -> Result<()> {
 
                                 // log fps
                                 if (now - last_frames).as_secs() >= 1 {
-                                    log::info!("fps: {}", frames);
+                                    log::info!("fps: {}, {}", frames, state.stats());
 
                                     frames = 0;
                                     last_frames = now;
@@ -115,13 +153,6 @@ pub async fn result_runner() -> Result<()> {
                                 window.request_redraw();
                             }
 
-                            #[cfg(target_arch = "wasm32")]
-                            WindowEvent::MouseInput { .. } => {
-                                // hide and lock cursor
-                                window.set_cursor_grab(winit::window::CursorGrabMode::Locked).unwrap();
-                                window.set_cursor_visible(false);
-                            }
-
                             _ => {},
                         }
                     }
@@ -146,7 +177,7 @@ pub async fn result_runner() -> Result<()> {
         } else if let Event::WindowEvent { event: WindowEvent::Resized(_), .. } = event {
             // create state on first resize
             state = Some(
-                pollster::block_on(State::new(&window)).unwrap()
+                pollster::block_on(State::new(&window, &options, storage.clone())).unwrap()
             );
         }
     });
@@ -0,0 +1,93 @@
+use noise::permutationtable::PermutationTable;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use std::fmt;
+use std::str::FromStr;
+use std::convert::Infallible;
+
+// simplex octave steps
+const OCTAVES: [u8; 5] = [1, 2, 4, 8, 16];
+
+// world seed
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WorldSeed(pub u64);
+
+impl WorldSeed {
+    // create seed
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    // create random seed
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+
+    // derive seed for named stream
+    pub fn derive(&self, stream: &str) -> u64 {
+        splitmix(self.0 ^ splitmix(fnv(stream)))
+    }
+
+    // create rng for named stream
+    pub fn rng(&self, stream: &str) -> StdRng {
+        StdRng::seed_from_u64(self.derive(stream))
+    }
+
+    // create rng for named stream at grid position
+    pub fn rng_at(&self, stream: &str, pos: (i32, i32, i32)) -> StdRng {
+        let seed = [pos.0, pos.1, pos.2].into_iter().fold(self.derive(stream), |hash, coord| {
+            splitmix(hash ^ coord as u32 as u64)
+        });
+
+        StdRng::seed_from_u64(seed)
+    }
+
+    // create simplex hashers
+    pub fn hashers(&self) -> Vec<(u8, PermutationTable)> {
+        OCTAVES
+            .iter()
+            .map(|&step| {
+                let seed = self.derive(&format!("height_{}", step));
+                (step, PermutationTable::new(seed as u32))
+            }).collect()
+    }
+}
+
+impl FromStr for WorldSeed {
+    type Err = Infallible;
+
+    // parse number or hash text
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+
+        Ok(Self(
+            text.parse::<u64>()
+                .or_else(|_| text.parse::<i64>().map(|num| num as u64))
+                .unwrap_or_else(|_| fnv(text))
+        ))
+    }
+}
+
+impl fmt::Display for WorldSeed {
+    // show as number
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// fnv-1a string hash
+fn fnv(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+// splitmix64 mixer
+fn splitmix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
@@ -4,13 +4,17 @@ struct VertexInput {
   @location(0)
   pos : vec4<f32>,
 
-  // texture position
+  // texture position in tile
   @location(1)
   tex_pos : vec2<f32>,
 
   // face index
   @location(2)
   face : u32,
+
+  // cube vertex index
+  @builtin(vertex_index)
+  index : u32,
 };
 
 // instance input
@@ -19,13 +23,21 @@ struct InstanceInput {
   @location(3)
   pos : vec4<f32>,
 
-  // texture
+  // face tiles, two per word
   @location(4)
-  tex : f32,
+  tiles : vec3<u32>,
 
   // faces to render
   @location(5)
   faces : u32,
+
+  // packed face light, four faces per word
+  @location(6)
+  light : vec2<u32>,
+
+  // face corner brightness, two bits per corner and four faces per word
+  @location(7)
+  ao : vec2<u32>,
 }
 
 // vertex output
@@ -45,8 +57,88 @@ struct VertexOutput {
   // should render
   @location(2)
   render : u32,
+
+  // sky and block light
+  @location(3)
+  light : vec2<f32>,
+
+  // brightness of all face corners
+  @location(4) @interpolate(flat)
+  ao : vec4<f32>,
+
+  // position between face corners
+  @location(5)
+  corner : vec2<f32>,
+};
+
+// mesh vertex input
+struct MeshInput {
+  // position
+  @location(0)
+  pos : vec4<f32>,
+
+  // texture position in blocks
+  @location(1)
+  tex_pos : vec2<f32>,
+
+  // texture tile index
+  @location(2)
+  tile : u32,
+
+  // face index
+  @location(3)
+  face : u32,
+
+  // packed sky and block light
+  @location(4)
+  light : u32,
+
+  // corner brightness
+  @location(5)
+  ao : u32,
+};
+
+// mesh vertex output
+struct MeshOutput {
+  // clip position
+  @builtin(position)
+  clip_pos : vec4<f32>,
+
+  // texture position in blocks
+  @location(0)
+  tex_pos : vec2<f32>,
+
+  // texture tile origin
+  @location(1)
+  tile : vec2<f32>,
+
+  // face index
+  @location(2)
+  face : u32,
+
+  // sky and block light
+  @location(3)
+  light : vec2<f32>,
+
+  // corner brightness
+  @location(4)
+  ao : f32,
 };
 
+// atlas tiles
+const ATLAS_SIZE = vec2<u32>(6u, 8u);
+const TILE_SIZE  = vec2<f32>(1.0 / 6.0, 1.0 / 8.0);
+
+// atlas position of tile
+fn tile_origin(tile : u32) -> vec2<f32> {
+  return vec2<f32>(vec2<u32>(tile % ATLAS_SIZE.x, tile / ATLAS_SIZE.x)) * TILE_SIZE;
+}
+
+// sky and block light from packed levels
+fn unpack_light(packed : u32) -> vec2<f32> {
+  return vec2<f32>(f32((packed >> 4u) & 0xfu), f32(packed & 0xfu)) / 15.0;
+}
+
 // camera uniform
 struct CameraUniform {
   view_proj : mat4x4<f32>,
@@ -55,22 +147,148 @@ struct CameraUniform {
 @group(1) @binding(0)
 var<uniform> camera : CameraUniform;
 
+// sky uniform
+struct SkyUniform {
+  // view projection without camera position, and its inverse
+  view_proj     : mat4x4<f32>,
+  inv_view_proj : mat4x4<f32>,
+
+  // sun direction and sky light scale
+  sun : vec4<f32>,
+
+  // gradient colours
+  zenith  : vec4<f32>,
+  horizon : vec4<f32>,
+};
+
+@group(2) @binding(0)
+var<uniform> sky : SkyUniform;
+
+// sky vertex output
+struct SkyOutput {
+  // clip position
+  @builtin(position)
+  clip_pos : vec4<f32>,
+
+  // screen position
+  @location(0)
+  screen : vec2<f32>,
+};
+
+// sun and moon vertex output
+struct BodyOutput {
+  // clip position
+  @builtin(position)
+  clip_pos : vec4<f32>,
+
+  // position on disc
+  @location(0)
+  disc : vec2<f32>,
+
+  // sun or moon
+  @location(1)
+  body : u32,
+
+  // visibility above horizon
+  @location(2)
+  fade : f32,
+};
+
 // vertex shader
 @vertex
 fn vtx_main(in : VertexInput, instance : InstanceInput) -> VertexOutput {
   var out : VertexOutput;
 
+  // unpack face tile
+  var tile = (instance.tiles[in.face / 2u] >> ((in.face % 2u) * 16u)) & 0xffffu;
+
   // alter view based on projection
   out.clip_pos = camera.view_proj * (in.pos + instance.pos);
-  out.tex_pos  = vec2<f32>(in.tex_pos.x, in.tex_pos.y + instance.tex);
+  out.tex_pos  = tile_origin(tile) + in.tex_pos * TILE_SIZE;
 
   // render face if in faces
   out.face   = in.face;
   out.render = (1u << in.face) & instance.faces;
 
+  // unpack face light
+  out.light = unpack_light(instance.light[in.face / 4u] >> ((in.face % 4u) * 8u));
+
+  // unpack face corner brightness, blended per fragment so quads shade evenly
+  var ao     = instance.ao[in.face / 4u] >> ((in.face % 4u) * 8u);
+  var corner = in.index % 4u;
+
+  out.ao     = vec4<f32>(vec4<u32>(ao, ao >> 2u, ao >> 4u, ao >> 6u) & vec4<u32>(3u));
+  out.corner = vec2<f32>(f32(corner & 1u), f32(corner >> 1u));
+
+  return out;
+}
+
+// mesh vertex shader
+@vertex
+fn vtx_mesh(in : MeshInput) -> MeshOutput {
+  var out : MeshOutput;
+
+  // alter view based on projection
+  out.clip_pos = camera.view_proj * in.pos;
+  out.tex_pos  = in.tex_pos;
+  out.tile     = tile_origin(in.tile);
+  out.face     = in.face;
+  out.light    = unpack_light(in.light);
+  out.ao       = f32(in.ao);
+
+  return out;
+}
+
+// sky vertex shader, one triangle covering the screen
+@vertex
+fn vtx_sky(@builtin(vertex_index) index : u32) -> SkyOutput {
+  var out : SkyOutput;
+
+  var pos = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u)) * 2.0 - 1.0;
+
+  // in front of cleared depth
+  out.clip_pos = vec4<f32>(pos, 0.0, 1.0);
+  out.screen   = pos;
+
+  return out;
+}
+
+// sun and moon vertex shader, moon opposite sun
+@vertex
+fn vtx_body(@builtin(vertex_index) index : u32, @builtin(instance_index) body : u32) -> BodyOutput {
+  var out : BodyOutput;
+
+  // quad corners
+  var corners = array<vec2<f32>, 6>(
+    vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(-1.0, 1.0),
+    vec2<f32>(-1.0, 1.0),  vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+  );
+
+  var disc = corners[index];
+  var dir  = sky.sun.xyz * select(1.0, -1.0, body == 1u);
+  var size = select(0.09, 0.06, body == 1u);
+
+  // face billboard towards eye
+  var right = normalize(cross(dir, vec3<f32>(0.0, 0.0, 1.0)));
+  var up    = cross(right, dir);
+
+  out.clip_pos   = sky.view_proj * vec4<f32>(dir + (right * disc.x + up * disc.y) * size, 1.0);
+  out.clip_pos.z = 0.0;
+
+  out.disc = disc;
+  out.body = body;
+  out.fade = smoothstep(-0.15, 0.0, dir.y);
+
   return out;
 }
 
+// outline vertex shader
+@vertex
+fn vtx_outline(@location(0) pos : vec4<f32>) -> @builtin(position) vec4<f32> {
+  // alter view based on projection
+  return camera.view_proj * pos;
+}
+
 // texture
 @group(0) @binding(0)
 var t_diffuse : texture_2d<f32>;
@@ -100,6 +318,17 @@ fn lighten(face : u32) -> f32 {
   }
 }
 
+// brightness of brighter light, each level dimmer than the last
+fn brightness(light : vec2<f32>) -> f32 {
+  var level = max(light.x * sky.sun.w, light.y);
+  return mix(0.06, 1.0, pow(0.8, (1.0 - level) * 15.0));
+}
+
+// darken corners hidden by blocks around them
+fn occlusion(ao : f32) -> f32 {
+  return 0.4 + 0.2 * ao;
+}
+
 // fragment shader
 @fragment
 fn frag_main(in : VertexOutput) -> @location(0) vec4<f32> {
@@ -111,9 +340,100 @@ fn frag_main(in : VertexOutput) -> @location(0) vec4<f32> {
   // sample texture
   var texture = textureSample(t_diffuse, s_diffuse, in.tex_pos);
 
-  // lighten based on face
-  var amount = lighten(in.face);
+  // cut out transparent texels, translucent blocks blend in frag_blend
+  if texture.a < 0.5 {
+    discard;
+  }
+
+  // blend brightness of face corners
+  var edge = mix(in.ao.xz, in.ao.yw, in.corner.x);
+  var ao   = mix(edge.x, edge.y, in.corner.y);
+
+  // lighten based on face, light and corners
+  var amount = lighten(in.face) * brightness(in.light) * occlusion(ao);
+  texture *= vec4<f32>(amount, amount, amount, 1.0);
+
+  return texture;
+}
+
+// mesh fragment shader
+@fragment
+fn frag_mesh(in : MeshOutput) -> @location(0) vec4<f32> {
+  // repeat tile across quad
+  var tex_pos = in.tile + fract(in.tex_pos) * TILE_SIZE;
+  var texture = textureSample(t_diffuse, s_diffuse, tex_pos);
+
+  // cut out transparent texels, translucent blocks blend in frag_blend
+  if texture.a < 0.5 {
+    discard;
+  }
+
+  // lighten based on face, light and corners
+  var amount = lighten(in.face) * brightness(in.light) * occlusion(in.ao);
+  texture *= vec4<f32>(amount, amount, amount, 1.0);
+
+  return texture;
+}
+
+// blended mesh fragment shader
+@fragment
+fn frag_blend(in : MeshOutput) -> @location(0) vec4<f32> {
+  // repeat tile across quad
+  var tex_pos = in.tile + fract(in.tex_pos) * TILE_SIZE;
+  var texture = textureSample(t_diffuse, s_diffuse, tex_pos);
+
+  // skip empty texels, keep faint ones
+  if texture.a == 0.0 {
+    discard;
+  }
+
+  // lighten based on face and light
+  var amount = lighten(in.face) * brightness(in.light);
   texture *= vec4<f32>(amount, amount, amount, 1.0);
 
   return texture;
 }
+
+// sky fragment shader
+@fragment
+fn frag_sky(in : SkyOutput) -> @location(0) vec4<f32> {
+  // view direction through pixel
+  var far = sky.inv_view_proj * vec4<f32>(in.screen, 1.0, 1.0);
+  var dir = normalize(far.xyz / far.w);
+
+  // blend from horizon up, below it stays horizon colour
+  var colour = mix(sky.horizon.rgb, sky.zenith.rgb, sqrt(max(dir.y, 0.0)));
+
+  // glow around sun
+  var glow = pow(max(dot(dir, sky.sun.xyz), 0.0), 48.0) * 0.35;
+  colour += vec3<f32>(1.0, 0.8, 0.55) * glow * smoothstep(-0.2, 0.1, sky.sun.y);
+
+  return vec4<f32>(colour, 1.0);
+}
+
+// sun and moon fragment shader
+@fragment
+fn frag_body(in : BodyOutput) -> @location(0) vec4<f32> {
+  var dist = length(in.disc);
+
+  if dist > 1.0 {
+    discard;
+  }
+
+  // soft edged discs, sinking behind horizon
+  var alpha = smoothstep(1.0, 0.8, dist) * in.fade;
+
+  if in.body == 0u {
+    return vec4<f32>(1.0, 0.95, 0.75, alpha);
+  }
+
+  // shade moon towards one side
+  var shade = mix(0.95, 0.7, (in.disc.x + 1.0) * 0.5);
+  return vec4<f32>(vec3<f32>(0.85, 0.87, 0.95) * shade, alpha);
+}
+
+// outline fragment shader
+@fragment
+fn frag_outline() -> @location(0) vec4<f32> {
+  return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+}
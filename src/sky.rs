@@ -0,0 +1,209 @@
+use crate::camera::Camera;
+use crate::projection::Projection;
+
+use bytemuck::{Pod, Zeroable};
+use anyhow::{Result, ensure};
+
+use cgmath::{Point3, Vector3, Matrix4, SquareMatrix, InnerSpace};
+
+use std::f32::consts::TAU;
+
+// real seconds per day at normal speed
+pub const DAY_LENGTH: f32 = 1200.0;
+
+// sky light scale at night
+pub const MIN_DAYLIGHT: f32 = 0.2;
+
+// gradient colours by day
+const DAY_ZENITH:  [f32; 3] = [0.1, 0.4, 0.7];
+const DAY_HORIZON: [f32; 3] = [0.45, 0.7, 0.9];
+
+// gradient colours by night
+const NIGHT_ZENITH:  [f32; 3] = [0.005, 0.008, 0.025];
+const NIGHT_HORIZON: [f32; 3] = [0.02, 0.03, 0.07];
+
+// horizon colour at sunrise and sunset
+const TWILIGHT: [f32; 3] = [0.9, 0.45, 0.2];
+
+// world clock driving day and night
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WorldTime {
+    // fraction of day passed, midnight at 0 and noon at a half
+    day_time: f32,
+
+    // real seconds per day at normal speed
+    day_length: f32,
+
+    // clock rate
+    speed: f32,
+
+    // stopped clock
+    paused: bool,
+}
+
+impl WorldTime {
+    // create running clock
+    pub fn new(day_time: f32, day_length: f32) -> Result<Self> {
+        ensure!(day_time.is_finite(), "invalid time of day: {}", day_time);
+        ensure!(day_length.is_finite() && day_length > 0.0, "invalid day length: {}", day_length);
+
+        Ok(Self {
+            day_time: day_time.rem_euclid(1.0),
+            day_length,
+            speed:    1.0,
+            paused:   false,
+        })
+    }
+
+    // fraction of day passed
+    pub fn day_time(&self) -> f32 {
+        self.day_time
+    }
+
+    // jump to fraction of day, wrapping into one day
+    pub fn set_day_time(&mut self, day_time: f32) {
+        if day_time.is_finite() {
+            self.day_time = day_time.rem_euclid(1.0);
+        }
+    }
+
+    // real seconds per day at normal speed
+    pub fn day_length(&self) -> f32 {
+        self.day_length
+    }
+
+    // clock rate
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    // change clock rate
+    pub fn set_speed(&mut self, speed: f32) -> Result<()> {
+        ensure!(speed.is_finite() && speed >= 0.0, "invalid time speed: {}", speed);
+
+        self.speed = speed;
+        Ok(())
+    }
+
+    // check if clock is stopped
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    // stop or resume clock
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    // advance clock by elapsed seconds
+    pub fn advance(&mut self, dt: f32) {
+        if !self.paused {
+            self.set_day_time(self.day_time + dt * self.speed / self.day_length);
+        }
+    }
+
+    // unit vector towards sun, rising along x and leaning towards z
+    pub fn sun_direction(&self) -> Vector3<f32> {
+        let (sin, cos) = ((self.day_time - 0.25) * TAU).sin_cos();
+        Vector3::new(cos, sin, 0.25).normalize()
+    }
+
+    // share of full daylight from 0 at night to 1 by day
+    fn day(&self) -> f32 {
+        smoothstep(-0.1, 0.15, self.sun_direction().y)
+    }
+
+    // sky light scale
+    pub fn daylight(&self) -> f32 {
+        MIN_DAYLIGHT + (1.0 - MIN_DAYLIGHT) * self.day()
+    }
+
+    // gradient colours at top of sky and horizon
+    pub fn sky_colours(&self) -> ([f32; 3], [f32; 3]) {
+        let day = self.day();
+
+        // redden horizon while sun is near it
+        let twilight = (1.0 - (self.sun_direction().y / 0.25).abs()).max(0.0);
+
+        let zenith  = mix(NIGHT_ZENITH, DAY_ZENITH, day);
+        let horizon = mix(mix(NIGHT_HORIZON, DAY_HORIZON, day), TWILIGHT, twilight * 0.7);
+
+        (zenith, horizon)
+    }
+}
+
+impl Default for WorldTime {
+    // morning clock
+    fn default() -> Self {
+        Self {
+            day_time:   0.3,
+            day_length: DAY_LENGTH,
+            speed:      1.0,
+            paused:     false,
+        }
+    }
+}
+
+// sky data in shaders
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct SkyUniform {
+    // view projection without camera position, and its inverse
+    view_proj:     [[f32; 4]; 4],
+    inv_view_proj: [[f32; 4]; 4],
+
+    // sun direction and sky light scale
+    sun: [f32; 4],
+
+    // gradient colours
+    zenith:  [f32; 4],
+    horizon: [f32; 4],
+}
+
+impl SkyUniform {
+    // create uniform
+    pub fn new() -> Self {
+        Self {
+            view_proj:     Matrix4::identity().into(),
+            inv_view_proj: Matrix4::identity().into(),
+
+            sun:     [0.0, 1.0, 0.0, 1.0],
+            zenith:  [0.0; 4],
+            horizon: [0.0; 4],
+        }
+    }
+
+    // update view and sky from clock
+    pub fn update(&mut self, camera: &Camera, projection: &Projection, time: &WorldTime) {
+        let view      = Matrix4::look_to_rh(Point3::new(0.0, 0.0, 0.0), camera.direction(), Vector3::unit_y());
+        let view_proj = projection.calc_matrix() * view;
+
+        self.view_proj     = view_proj.into();
+        self.inv_view_proj = view_proj.invert().unwrap_or(Matrix4::identity()).into();
+
+        let sun = time.sun_direction();
+        self.sun = [sun.x, sun.y, sun.z, time.daylight()];
+
+        let (zenith, horizon) = time.sky_colours();
+        self.zenith  = [zenith[0], zenith[1], zenith[2], 1.0];
+        self.horizon = [horizon[0], horizon[1], horizon[2], 1.0];
+    }
+}
+
+impl Default for SkyUniform {
+    // default uniform
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// smooth step from 0 below edge to 1 above other edge
+fn smoothstep(low: f32, high: f32, value: f32) -> f32 {
+    let t = ((value - low) / (high - low)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// blend colours
+fn mix(from: [f32; 3], to: [f32; 3], amount: f32) -> [f32; 3] {
+    [0, 1, 2].map(|idx| from[idx] + (to[idx] - from[idx]) * amount)
+}
@@ -1,17 +1,46 @@
 use crate::vertex::{get_vertices, Vertex, INDICES};
 use crate::texture::Texture;
 use crate::camera::{Camera, CameraUniform};
+use crate::sky::{SkyUniform, WorldTime};
 use crate::projection::Projection;
 use crate::camera_controller::CameraController;
+use crate::player::Player;
 use crate::instance::Instance;
-use crate::world::World;
-
-use winit::event::WindowEvent;
+use crate::mesh::{MeshVertex, Mesher};
+use crate::chunk::{ChunkPos, CHUNK_SIZE};
+use crate::chunk_buffers::{ChunkBuffers, TranslucentBuffers};
+use crate::outline::{Outline, OutlineVertex, OUTLINE_VERTICES};
+use crate::world::{World, WorldStats, WorldHeight, Eviction};
+use crate::block::{Block, BlockRegistry};
+use crate::generator::Caves;
+use crate::options::Options;
+use crate::seed::WorldSeed;
+use crate::storage::ChunkStorage;
+
+use winit::event::{WindowEvent, KeyEvent, ElementState, MouseButton};
+use winit::keyboard::{KeyCode, PhysicalKey};
 use winit::window::Window;
 
 use wgpu::util::DeviceExt;
 use anyhow::{Result, Context};
 
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// block interaction distance
+const REACH: f32 = 6.0;
+
+// clock rates reachable with speed keys
+const MIN_TIME_SPEED: f32 = 0.125;
+const MAX_TIME_SPEED: f32 = 256.0;
+
+// block selection keys
+const SELECT_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3,
+    KeyCode::Digit4, KeyCode::Digit5, KeyCode::Digit6,
+    KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+];
+
 // app state
 pub struct State {
     // surface
@@ -29,17 +58,28 @@ pub struct State {
     // window size
     size: winit::dpi::PhysicalSize<u32>,
 
-    // render pipeline
-    pipeline: wgpu::RenderPipeline,
+    // render pipelines
+    pipeline:             wgpu::RenderPipeline,
+    mesh_pipeline:        wgpu::RenderPipeline,
+    translucent_pipeline: wgpu::RenderPipeline,
+    outline_pipeline:     wgpu::RenderPipeline,
+    sky_pipeline:         wgpu::RenderPipeline,
+    body_pipeline:        wgpu::RenderPipeline,
 
     // buffers
     vtx_buf: wgpu::Buffer,
     idx_buf: wgpu::Buffer,
 
-    // world instances
-    world:        World,
-    instances:    Vec<Instance>,
-    instance_buf: wgpu::Buffer,
+    // world geometry
+    world:               World,
+    chunk_buffers:       HashMap<ChunkPos, ChunkBuffers>,
+    translucent_buffers: HashMap<ChunkPos, TranslucentBuffers>,
+
+    // block to place
+    selected: Block,
+
+    // targeted block outline
+    outline: Outline,
 
     // textures
     depth_texture:   Texture,
@@ -50,13 +90,23 @@ pub struct State {
     projection:            Projection,
     pub camera_controller: CameraController,
     camera_uniform:        CameraUniform,
+    player:                Player,
     camera_buf:            wgpu::Buffer,
     camera_bind_group:     wgpu::BindGroup,
+
+    // sky
+    sky_uniform:    SkyUniform,
+    sky_buf:        wgpu::Buffer,
+    sky_bind_group: wgpu::BindGroup,
 }
 
 impl State {
     // create state
-    pub async fn new(window: &Window) -> Result<Self> {
+    pub async fn new(
+        window:  &Window,
+        options: &Options,
+        storage: Option<Arc<dyn ChunkStorage>>,
+    ) -> Result<Self> {
         // window size
         let size = window.inner_size();
 
@@ -147,6 +197,14 @@ impl State {
             camera_bind_group,
         ) = Self::create_camera(&projection, &device);
 
+        // create sky
+        let (
+            sky_uniform,
+            sky_buf,
+            sky_bind_group_layout,
+            sky_bind_group,
+        ) = Self::create_sky(&device);
+
         // create camera controller
         let camera_controller = CameraController::new(12.5, 0.5);
 
@@ -160,17 +218,84 @@ impl State {
             &device, &config, "depth_texture"
         );
 
-        // create pipeline
+        // create pipelines
+        let layouts = [
+            &diffuse_bind_group_layout,
+            &camera_bind_group_layout,
+            &sky_bind_group_layout,
+        ];
+
         let pipeline = Self::create_pipeline(
             &shader,
             &device,
             &config,
-            &[
-                &diffuse_bind_group_layout,
-                &camera_bind_group_layout,
-            ],
+            &layouts,
+            ("vtx_main", "frag_main"),
+            &[Vertex::layout(), Instance::layout()],
+            wgpu::PrimitiveTopology::TriangleList,
+            false,
+        );
+
+        let mesh_pipeline = Self::create_pipeline(
+            &shader,
+            &device,
+            &config,
+            &layouts,
+            ("vtx_mesh", "frag_mesh"),
+            &[MeshVertex::layout()],
+            wgpu::PrimitiveTopology::TriangleList,
+            false,
+        );
+
+        let translucent_pipeline = Self::create_pipeline(
+            &shader,
+            &device,
+            &config,
+            &layouts,
+            ("vtx_mesh", "frag_blend"),
+            &[MeshVertex::layout()],
+            wgpu::PrimitiveTopology::TriangleList,
+            true,
+        );
+
+        let outline_pipeline = Self::create_pipeline(
+            &shader,
+            &device,
+            &config,
+            &layouts,
+            ("vtx_outline", "frag_outline"),
+            &[OutlineVertex::layout()],
+            wgpu::PrimitiveTopology::LineList,
+            false,
         );
 
+        // sky behind everything, without depth writes
+        let sky_pipeline = Self::create_pipeline(
+            &shader,
+            &device,
+            &config,
+            &layouts,
+            ("vtx_sky", "frag_sky"),
+            &[],
+            wgpu::PrimitiveTopology::TriangleList,
+            true,
+        );
+
+        // sun and moon blended over sky
+        let body_pipeline = Self::create_pipeline(
+            &shader,
+            &device,
+            &config,
+            &layouts,
+            ("vtx_body", "frag_body"),
+            &[],
+            wgpu::PrimitiveTopology::TriangleList,
+            true,
+        );
+
+        // create outline
+        let outline = Outline::new(&device);
+
         // create vertex buffer
         let vtx_buf = device.create_buffer_init(
             &wgpu::util::BufferInitDescriptor {
@@ -189,9 +314,71 @@ impl State {
             }
         );
 
-        // create world instances
-        let mut world = World::new();
-        let (instances, instance_buf) = Self::create_instance_buf(&mut world, &camera, &device);
+        // seed of saved world, else requested or random one
+        let seed = match &storage {
+            Some(storage) => match storage.read_seed()? {
+                Some(seed) => seed,
+
+                None => {
+                    let seed = options.seed.unwrap_or_else(WorldSeed::random);
+                    storage.write_seed(seed)?;
+
+                    seed
+                }
+            },
+
+            None => options.seed.unwrap_or_else(WorldSeed::random),
+        };
+
+        log::info!("seed: {}", seed);
+
+        // create world
+        let registry  = Self::create_registry(options)?;
+        let mut world = World::with_registry(seed, registry)?;
+
+        if let Some(storage) = storage {
+            world.set_storage(storage);
+        }
+
+        // vertical bounds
+        let height = WorldHeight::default();
+
+        world.set_height(WorldHeight::new(
+            options.min_y.unwrap_or(height.min_y()),
+            options.max_y.unwrap_or(height.max_y()),
+        )?);
+
+        // underground carving
+        let caves = Caves::default();
+
+        world.set_caves(Caves {
+            threshold: options.cave_threshold.unwrap_or(caves.threshold),
+            tunnels:   options.tunnel_width.unwrap_or(caves.tunnels),
+            overhangs: options.overhangs.unwrap_or(caves.overhangs),
+        })?;
+
+        world.set_mesher(options.mesher);
+
+        // day and night clock
+        let time = WorldTime::default();
+
+        world.set_time(WorldTime::new(
+            options.time.map_or(time.day_time(), |hours| hours / 24.0),
+            options.day_length.unwrap_or(time.day_length()),
+        )?);
+
+        world.set_eviction(Eviction {
+            max_chunks: options.max_chunks,
+            max_memory: options.max_memory.map(|mib| mib * 1024 * 1024),
+            ..Default::default()
+        });
+
+        // stand on terrain below camera
+        let ground = world.surface(camera.pos.x.floor() as i32, camera.pos.z.floor() as i32);
+        let player = Player::new((camera.pos.x, ground as f32, camera.pos.z).into());
+
+        // first placeable block
+        let selected = Self::placeable(&world, 0).unwrap_or(Block::AIR);
 
         Ok(Self {
             surface,
@@ -200,13 +387,21 @@ impl State {
             config,
             size,
             pipeline,
+            mesh_pipeline,
+            translucent_pipeline,
+            outline_pipeline,
+            sky_pipeline,
+            body_pipeline,
 
             vtx_buf,
             idx_buf,
 
             world,
-            instances,
-            instance_buf,
+            chunk_buffers:       HashMap::new(),
+            translucent_buffers: HashMap::new(),
+
+            selected,
+            outline,
 
             depth_texture,
             diffuse_bind_group,
@@ -215,11 +410,27 @@ impl State {
             projection,
             camera_controller,
             camera_uniform,
+            player,
             camera_buf,
             camera_bind_group,
+
+            sky_uniform,
+            sky_buf,
+            sky_bind_group,
         })
     }
 
+    // create block registry
+    fn create_registry(options: &Options) -> Result<BlockRegistry> {
+        match &options.blocks {
+            // load definitions file
+            #[cfg(not(target_arch = "wasm32"))]
+            Some(path) => BlockRegistry::load(std::path::Path::new(path)),
+
+            _ => Ok(BlockRegistry::builtin()),
+        }
+    }
+
     // create texture
     fn create_texture(
         bytes:  &[u8],
@@ -339,12 +550,74 @@ impl State {
         (camera, uniform, buffer, bind_group_layout, bind_group)
     }
 
-    // create render pipeline
+    // create sky
+    fn create_sky(device: &wgpu::Device) -> (SkyUniform, wgpu::Buffer, wgpu::BindGroupLayout, wgpu::BindGroup) {
+        // filled in on first update
+        let uniform = SkyUniform::new();
+
+        // create sky buffer
+        let buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label:    Some("sky_buf"),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage:    wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+
+        // create bind group layout
+        let bind_group_layout = device.create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: Some("sky_bind_group_layout"),
+
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+
+                        // sky light scale is used by block fragments
+                        visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+
+                        // buffer type
+                        ty: wgpu::BindingType::Buffer {
+                            ty:                 wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size:   None,
+                        },
+
+                        count: None,
+                    }
+                ],
+            }
+        );
+
+        // create bind group
+        let bind_group = device.create_bind_group(
+            &wgpu::BindGroupDescriptor {
+                label:  Some("sky_bind_group"),
+                layout: &bind_group_layout,
+
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }
+                ],
+            }
+        );
+
+        (uniform, buffer, bind_group_layout, bind_group)
+    }
+
+    // create render pipeline, blending without depth writes if translucent
+    #[allow(clippy::too_many_arguments)]
     fn create_pipeline(
         shader:  &wgpu::ShaderModule,
         device:  &wgpu::Device,
         config:  &wgpu::SurfaceConfiguration,
         layouts: &[&wgpu::BindGroupLayout],
+        entries: (&str, &str),
+        buffers: &[wgpu::VertexBufferLayout],
+        topology: wgpu::PrimitiveTopology,
+        translucent: bool,
     ) -> wgpu::RenderPipeline {
         // create pipeline layout
         let pipeline_layout =
@@ -363,31 +636,43 @@ impl State {
                 // run vertex shader
                 vertex: wgpu::VertexState {
                     module:      shader,
-                    entry_point: "vtx_main",
-                    buffers:     &[Vertex::layout(), Instance::layout()],
+                    entry_point: entries.0,
+                    buffers,
                 },
 
                 // run fragment shader
                 fragment: Some(wgpu::FragmentState {
                     module: shader,
-                    entry_point: "frag_main",
+                    entry_point: entries.1,
 
                     targets: &[Some(wgpu::ColorTargetState {
                         format:     config.format,
-                        blend:      Some(wgpu::BlendState::REPLACE),
+                        blend:      Some(if translucent {
+                            wgpu::BlendState::ALPHA_BLENDING
+                        } else {
+                            wgpu::BlendState::REPLACE
+                        }),
                         write_mask: wgpu::ColorWrites::ALL,
                     })],
                 }),
 
                 primitive: wgpu::PrimitiveState {
-                    cull_mode: Some(wgpu::Face::Back),
+                    topology,
+
+                    // lines have no faces, fluid surfaces show from below
+                    cull_mode: if topology == wgpu::PrimitiveTopology::TriangleList && !translucent {
+                        Some(wgpu::Face::Back)
+                    } else {
+                        None
+                    },
+
                     ..Default::default()
                 },
 
                 // use depth texture
                 depth_stencil: Some(wgpu::DepthStencilState {
                     format: Texture::DEPTH_FORMAT,
-                    depth_write_enabled: true,
+                    depth_write_enabled: !translucent,
                     depth_compare: wgpu::CompareFunction::Less,
                     stencil:       wgpu::StencilState::default(),
                     bias:          wgpu::DepthBiasState::default(),
@@ -404,25 +689,46 @@ impl State {
         )
     }
 
-    // create instance buffer
-    fn create_instance_buf(
-        world:  &mut World,
-        camera: &Camera,
-        device: &wgpu::Device,
-    ) -> (Vec<Instance>, wgpu::Buffer) {
-        // get instances
-        let instances = world.instances(camera.pos.x as i32, camera.pos.z as i32);
-
-        // create buffer
-        let instance_buf = device.create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label:    Some("instance_buf"),
-                contents: bytemuck::cast_slice(&instances),
-                usage:    wgpu::BufferUsages::VERTEX,
-            }
+    // upload rebuilt chunks
+    fn upload_chunks(&mut self) {
+        self.world.refresh(
+            self.camera.pos.x.floor() as i32,
+            self.camera.pos.y.floor() as i32,
+            self.camera.pos.z.floor() as i32,
         );
 
-        (instances, instance_buf)
+        // free unloaded chunks
+        for pos in self.world.take_unloaded() {
+            self.chunk_buffers.remove(&pos);
+            self.translucent_buffers.remove(&pos);
+        }
+
+        let eye = self.camera.pos.into();
+
+        for (pos, mesh) in self.world.take_meshes() {
+            match ChunkBuffers::new(&mesh.solid, &self.device) {
+                Some(buffers) => self.chunk_buffers.insert(pos, buffers),
+                None          => self.chunk_buffers.remove(&pos),
+            };
+
+            let size   = CHUNK_SIZE as f32;
+            let center = [pos.0, pos.1, pos.2].map(|coord| coord as f32 * size + size / 2.0);
+
+            match TranslucentBuffers::new(mesh.translucent, center, eye, &self.device) {
+                Some(buffers) => self.translucent_buffers.insert(pos, buffers),
+                None          => self.translucent_buffers.remove(&pos),
+            };
+        }
+
+        // keep blended faces ordered for the eye
+        for buffers in self.translucent_buffers.values_mut() {
+            buffers.sort(eye, &self.queue);
+        }
+    }
+
+    // world chunk counters
+    pub fn stats(&self) -> WorldStats {
+        self.world.stats()
     }
 
     // resize by scale factor
@@ -460,13 +766,177 @@ impl State {
 
     // handle window event
     pub fn event(&mut self, event: &WindowEvent) -> bool {
-        self.camera_controller.event(event)
+        if let WindowEvent::KeyboardInput {
+            event: KeyEvent {
+                state,
+                physical_key: PhysicalKey::Code(KeyCode::KeyM),
+                repeat: false,
+                ..
+            },
+            ..
+        } = event {
+            if state.is_pressed() {
+                // switch mesher
+                let mesher = self.world.mesher().toggle();
+                log::info!("mesher: {:?}", mesher);
+
+                self.world.set_mesher(mesher);
+            }
+
+            return true;
+        }
+
+        match event {
+            // download saved world
+            #[cfg(target_arch = "wasm32")]
+            WindowEvent::KeyboardInput {
+                event: KeyEvent {
+                    state: ElementState::Pressed,
+                    physical_key: PhysicalKey::Code(KeyCode::KeyE),
+                    repeat: false,
+                    ..
+                },
+                ..
+            } => {
+                if let Err(err) = self.export() {
+                    log::error!("failed to export world: {:#}", err);
+                }
+
+                true
+            }
+
+            // break or place block
+            WindowEvent::MouseInput { state: ElementState::Pressed, button, .. } => {
+                self.interact(*button);
+                true
+            }
+
+            // pause or resume clock
+            WindowEvent::KeyboardInput {
+                event: KeyEvent {
+                    state: ElementState::Pressed,
+                    physical_key: PhysicalKey::Code(KeyCode::KeyT),
+                    repeat: false,
+                    ..
+                },
+                ..
+            } => {
+                let mut time = self.world.time();
+                time.set_paused(!time.paused());
+
+                log::info!("time paused: {}", time.paused());
+                self.world.set_time(time);
+
+                true
+            }
+
+            // slow down or speed up clock
+            WindowEvent::KeyboardInput {
+                event: KeyEvent {
+                    state: ElementState::Pressed,
+                    physical_key: PhysicalKey::Code(code @ (KeyCode::BracketLeft | KeyCode::BracketRight)),
+                    ..
+                },
+                ..
+            } => {
+                let mut time = self.world.time();
+                let factor   = if *code == KeyCode::BracketRight { 2.0 } else { 0.5 };
+
+                if time.set_speed((time.speed() * factor).clamp(MIN_TIME_SPEED, MAX_TIME_SPEED)).is_ok() {
+                    log::info!("time speed: {}", time.speed());
+                    self.world.set_time(time);
+                }
+
+                true
+            }
+
+            // select block
+            WindowEvent::KeyboardInput {
+                event: KeyEvent {
+                    state: ElementState::Pressed,
+                    physical_key: PhysicalKey::Code(code),
+                    ..
+                },
+                ..
+            } if SELECT_KEYS.contains(code) => {
+                let index = SELECT_KEYS.iter().position(|key| key == code).unwrap();
+
+                if let Some(block) = Self::placeable(&self.world, index) {
+                    log::info!("selected: {}", self.world.registry().get(block).name);
+                    self.selected = block;
+                }
+
+                true
+            }
+
+            _ => self.camera_controller.event(event),
+        }
+    }
+
+    // download world for native use
+    #[cfg(target_arch = "wasm32")]
+    fn export(&mut self) -> Result<()> {
+        self.world.save()?;
+
+        let storage = self.world.storage().context("world is not saved")?;
+        crate::indexed_db::download("world.blocks", &crate::storage::export_world(storage)?)
+    }
+
+    // nth placeable block, skipping flowing fluid levels
+    fn placeable(world: &World, index: usize) -> Option<Block> {
+        let registry = world.registry();
+
+        registry
+            .iter()
+            .map(|(block, _)| block)
+            .filter(|&block| !block.is_air() && registry.fluid(block).is_none_or(|fluid| fluid.is_source()))
+            .nth(index)
+    }
+
+    // break or place targeted block
+    fn interact(&mut self, button: MouseButton) {
+        let Some(hit) = self.world.raycast(self.camera.pos, self.camera.direction(), REACH) else {
+            return;
+        };
+
+        match button {
+            MouseButton::Left => {
+                self.world.break_block(&hit);
+            }
+
+            MouseButton::Right => {
+                self.world.place_block(&hit, self.selected, &self.player.bounds());
+            }
+
+            _ => {}
+        }
+    }
+
+    // save changed chunks
+    pub fn save(&mut self) -> Result<()> {
+        self.world.save()
     }
 
     // handle updates
     pub fn update(&mut self, dt: instant::Duration) {
         // update camera
         self.camera_controller.update_camera(&mut self.camera, dt);
+
+        if self.camera_controller.spectator() {
+            // follow flying camera
+            self.player.set_eye(self.camera.pos);
+        } else {
+            self.player.walk(
+                &self.world,
+                self.camera_controller.direction(&self.camera),
+                self.camera_controller.jumping(),
+                self.camera_controller.sprinting(),
+                dt.as_secs_f32(),
+            );
+
+            self.camera.pos = self.player.eye();
+        }
+
         self.camera_uniform.update_view_proj(&self.camera, &self.projection);
 
         // write new uniform to buffer
@@ -475,11 +945,23 @@ impl State {
             bytemuck::cast_slice(&[self.camera_uniform])
         );
 
-        // update instances if required
-        if self.world.refresh_required(self.camera.pos.x as i32, self.camera.pos.z as i32) {
-            (self.instances, self.instance_buf) =
-                Self::create_instance_buf(&mut self.world, &self.camera, &self.device);
-        }
+        // advance clock, spread and drain fluids
+        self.world.tick(dt.as_secs_f32());
+
+        // follow clock and view direction in sky
+        self.sky_uniform.update(&self.camera, &self.projection, &self.world.time());
+
+        self.queue.write_buffer(
+            &self.sky_buf, 0,
+            bytemuck::cast_slice(&[self.sky_uniform])
+        );
+
+        // load and upload changed chunks
+        self.upload_chunks();
+
+        // outline targeted block
+        let target = self.world.raycast(self.camera.pos, self.camera.direction(), REACH);
+        self.outline.set_target(target.map(|hit| hit.pos), &self.queue);
     }
 
     // render window
@@ -497,6 +979,9 @@ impl State {
             }
         );
 
+        // clear to horizon behind sky
+        let (_, horizon) = self.world.time().sky_colours();
+
         {
             // begin render pass
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
@@ -511,9 +996,9 @@ impl State {
                         ops: wgpu::Operations {
                             load: wgpu::LoadOp::Clear(
                                 wgpu::Color {
-                                    r: 0.1,
-                                    g: 0.4,
-                                    b: 0.7,
+                                    r: horizon[0] as f64,
+                                    g: horizon[1] as f64,
+                                    b: horizon[2] as f64,
                                     a: 1.0,
                                 },
                             ),
@@ -541,23 +1026,87 @@ impl State {
                 occlusion_query_set: None,
             });
 
-            rpass.set_pipeline(&self.pipeline);
-
             // set bind groups
             rpass.set_bind_group(0, &self.diffuse_bind_group, &[]);
             rpass.set_bind_group(1, &self.camera_bind_group,  &[]);
+            rpass.set_bind_group(2, &self.sky_bind_group,     &[]);
+
+            // draw sky gradient, then sun and moon
+            rpass.set_pipeline(&self.sky_pipeline);
+            rpass.draw(0..3, 0..1);
 
-            // set vector buffer
-            rpass.set_vertex_buffer(0, self.vtx_buf.slice(..));
+            rpass.set_pipeline(&self.body_pipeline);
+            rpass.draw(0..6, 0..2);
 
-            // set instance buffer
-            rpass.set_vertex_buffer(1, self.instance_buf.slice(..));
+            let mesher = self.world.mesher();
 
-            // set index buffer
-            rpass.set_index_buffer(self.idx_buf.slice(..), wgpu::IndexFormat::Uint16);
+            match mesher {
+                Mesher::Instanced => {
+                    rpass.set_pipeline(&self.pipeline);
 
-            // draw instances
-            rpass.draw_indexed(0..INDICES.len() as u32, 0, 0..self.instances.len() as u32);
+                    // set vector buffer
+                    rpass.set_vertex_buffer(0, self.vtx_buf.slice(..));
+
+                    // set index buffer
+                    rpass.set_index_buffer(self.idx_buf.slice(..), wgpu::IndexFormat::Uint16);
+                }
+
+                Mesher::Greedy => {
+                    rpass.set_pipeline(&self.mesh_pipeline);
+                }
+            }
+
+            // draw visible chunks
+            for pos in self.world.visible() {
+                match self.chunk_buffers.get(&pos) {
+                    Some(ChunkBuffers::Instanced { buf, count }) if mesher == Mesher::Instanced => {
+                        // set instance buffer
+                        rpass.set_vertex_buffer(1, buf.slice(..));
+
+                        // draw instances
+                        rpass.draw_indexed(0..INDICES.len() as u32, 0, 0..*count);
+                    }
+
+                    Some(ChunkBuffers::Mesh { vtx_buf, idx_buf, count }) if mesher == Mesher::Greedy => {
+                        // set mesh buffers
+                        rpass.set_vertex_buffer(0, vtx_buf.slice(..));
+                        rpass.set_index_buffer(idx_buf.slice(..), wgpu::IndexFormat::Uint32);
+
+                        // draw mesh
+                        rpass.draw_indexed(0..*count, 0, 0..1);
+                    }
+
+                    _ => {}
+                }
+            }
+
+            // blend translucent chunks over opaque ones, farthest first
+            let eye = self.camera.pos.into();
+
+            let mut translucent =
+                self.world
+                    .visible()
+                    .filter_map(|pos| self.translucent_buffers.get(&pos))
+                    .collect::<Vec<_>>();
+
+            translucent.sort_by(|a, b| b.distance(eye).total_cmp(&a.distance(eye)));
+
+            rpass.set_pipeline(&self.translucent_pipeline);
+
+            for buffers in translucent {
+                rpass.set_vertex_buffer(0, buffers.vtx_buf.slice(..));
+                rpass.set_index_buffer(buffers.idx_buf.slice(..), wgpu::IndexFormat::Uint32);
+
+                rpass.draw_indexed(0..buffers.count, 0, 0..1);
+            }
+
+            // draw targeted block outline
+            if self.outline.target().is_some() {
+                rpass.set_pipeline(&self.outline_pipeline);
+                rpass.set_vertex_buffer(0, self.outline.buf.slice(..));
+
+                rpass.draw(0..OUTLINE_VERTICES, 0..1);
+            }
         }
 
         // submit to queue
@@ -0,0 +1,268 @@
+use crate::block::{Block, BlockRegistry};
+use crate::chunk::{Chunk, ChunkPos, CHUNK_SIZE};
+use crate::chunk_blocks::ChunkBlocks;
+use crate::seed::WorldSeed;
+use crate::options::Options;
+
+use anyhow::{Result, Context, bail, ensure};
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+// saved chunk format version
+pub const CHUNK_VERSION: u16 = 1;
+
+// exported world format version
+pub const ARCHIVE_VERSION: u16 = 1;
+
+// exported world signature
+const ARCHIVE_MAGIC: &[u8; 4] = b"BLKW";
+
+// saved chunk data at position
+pub type SavedChunk = (ChunkPos, Vec<u8>);
+
+// shared with background threads natively
+#[cfg(not(target_arch = "wasm32"))]
+pub trait StorageBounds: Send + Sync {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send + Sync> StorageBounds for T {}
+
+// browser handles stay on main thread
+#[cfg(target_arch = "wasm32")]
+pub trait StorageBounds {}
+
+#[cfg(target_arch = "wasm32")]
+impl<T> StorageBounds for T {}
+
+// saved chunk backend
+pub trait ChunkStorage: StorageBounds {
+    // read saved chunk data
+    fn read(&self, pos: ChunkPos) -> Result<Option<Vec<u8>>>;
+
+    // write chunk data
+    fn write(&self, pos: ChunkPos, data: &[u8]) -> Result<()>;
+
+    // write many chunks, batched where backend can
+    fn write_chunks(&self, chunks: &[SavedChunk]) -> Result<()> {
+        chunks.iter().try_for_each(|(pos, data)| self.write(*pos, data))
+    }
+
+    // read saved world seed
+    fn read_seed(&self) -> Result<Option<WorldSeed>>;
+
+    // write world seed
+    fn write_seed(&self, seed: WorldSeed) -> Result<()>;
+
+    // all saved chunks
+    fn entries(&self) -> Result<Vec<SavedChunk>>;
+}
+
+// open storage for world option
+pub async fn open(options: &Options) -> Result<Option<Arc<dyn ChunkStorage>>> {
+    let Some(world) = &options.world else {
+        if options.import.is_some() {
+            log::warn!("import requires a world");
+        }
+
+        return Ok(None);
+    };
+
+    // region files in directory
+    #[cfg(not(target_arch = "wasm32"))]
+    let storage = crate::region::RegionStorage::open(world)?;
+
+    // browser database
+    #[cfg(target_arch = "wasm32")]
+    let storage = crate::indexed_db::IndexedDbStorage::open(world).await?;
+
+    // unpack exported world
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(path) = &options.import {
+        let data = std::fs::read(path).with_context(|| format!("failed to read world: {}", path))?;
+        log::info!("imported seed: {}", import_world(&storage, &data)?);
+    }
+
+    Ok(Some(Arc::new(storage)))
+}
+
+// pack saved world into single file
+pub fn export_world(storage: &dyn ChunkStorage) -> Result<Vec<u8>> {
+    let seed    = storage.read_seed()?.context("world has no saved seed")?;
+    let entries = storage.entries()?;
+
+    let mut data = Vec::new();
+
+    // header
+    data.extend(ARCHIVE_MAGIC);
+    data.extend(ARCHIVE_VERSION.to_le_bytes());
+    data.extend(seed.0.to_le_bytes());
+    data.extend((entries.len() as u32).to_le_bytes());
+
+    // chunks as saved
+    for ((pos_x, pos_y, pos_z), chunk) in entries {
+        data.extend(pos_x.to_le_bytes());
+        data.extend(pos_y.to_le_bytes());
+        data.extend(pos_z.to_le_bytes());
+        data.extend((chunk.len() as u32).to_le_bytes());
+        data.extend(chunk);
+    }
+
+    Ok(data)
+}
+
+// unpack exported world into storage
+pub fn import_world(storage: &dyn ChunkStorage, data: &[u8]) -> Result<WorldSeed> {
+    // chunks of another seed would mix with imported ones
+    ensure!(
+        storage.read_seed()?.is_none() && storage.entries()?.is_empty(),
+        "world is not empty, import into a new world"
+    );
+
+    let mut reader = data;
+
+    ensure!(&take::<4>(&mut reader)? == ARCHIVE_MAGIC, "missing world header");
+
+    let version = u16::from_le_bytes(take(&mut reader)?);
+
+    if version != ARCHIVE_VERSION {
+        bail!("unsupported world version: {}", version);
+    }
+
+    let seed  = WorldSeed::new(u64::from_le_bytes(take(&mut reader)?));
+    let count = u32::from_le_bytes(take(&mut reader)?);
+
+    let mut chunks = Vec::with_capacity(count as usize);
+
+    for _ in 0..count {
+        let pos_x = i32::from_le_bytes(take(&mut reader)?);
+        let pos_y = i32::from_le_bytes(take(&mut reader)?);
+        let pos_z = i32::from_le_bytes(take(&mut reader)?);
+        let len   = u32::from_le_bytes(take(&mut reader)?) as usize;
+
+        ensure!(reader.len() >= len, "truncated world data");
+        let (chunk, rest) = reader.split_at(len);
+        reader = rest;
+
+        chunks.push(((pos_x, pos_y, pos_z), chunk.to_vec()));
+    }
+
+    // nothing is written from invalid archives
+    storage.write_chunks(&chunks)?;
+    storage.write_seed(seed)?;
+    Ok(seed)
+}
+
+// serialize chunk as palette and compressed blocks
+pub fn encode_chunk(chunk: &Chunk, registry: &BlockRegistry) -> Result<Vec<u8>> {
+    let mut palette = Vec::new();
+    let mut indices = HashMap::new();
+
+    // palette index per block
+    let blocks = chunk_positions().map(|(x, y, z)| {
+        let block = chunk.block(x, y, z);
+
+        *indices.entry(block).or_insert_with(|| {
+            palette.push(block);
+            palette.len() as u16 - 1
+        })
+    }).collect::<Vec<_>>();
+
+    let mut data = Vec::new();
+
+    // header
+    data.extend(CHUNK_VERSION.to_le_bytes());
+    data.extend(chunk.decorations().to_le_bytes());
+    data.extend((palette.len() as u16).to_le_bytes());
+
+    // palette names
+    for block in palette {
+        let name = registry.get(block).name.as_bytes();
+        ensure!(name.len() <= u8::MAX as usize, "block name too long");
+
+        data.push(name.len() as u8);
+        data.extend(name);
+    }
+
+    // compressed block indices
+    let mut encoder = DeflateEncoder::new(data, Compression::default());
+
+    for index in blocks {
+        encoder.write_all(&index.to_le_bytes())?;
+    }
+
+    Ok(encoder.finish()?)
+}
+
+// deserialize chunk at position
+pub fn decode_chunk(pos: ChunkPos, data: &[u8], registry: &BlockRegistry) -> Result<Chunk> {
+    let mut reader = data;
+
+    let version = u16::from_le_bytes(take(&mut reader)?);
+
+    if version != CHUNK_VERSION {
+        bail!("unsupported chunk version: {}", version);
+    }
+
+    // neighbours whose structures were placed
+    let decorated = u32::from_le_bytes(take(&mut reader)?);
+
+    // palette names
+    let palette_len = u16::from_le_bytes(take(&mut reader)?);
+
+    let palette = (0..palette_len).map(|_| {
+        let [len] = take(&mut reader)?;
+
+        ensure!(reader.len() >= len as usize, "truncated palette");
+        let (name, rest) = reader.split_at(len as usize);
+        reader = rest;
+
+        let name = std::str::from_utf8(name).context("invalid block name")?;
+
+        // removed block types become air
+        Ok(registry.id(name).unwrap_or_else(|| {
+            log::warn!("unknown saved block: {}", name);
+            Block::AIR
+        }))
+    }).collect::<Result<Vec<_>>>()?;
+
+    // compressed block indices
+    let mut blocks = Vec::new();
+    DeflateDecoder::new(reader).read_to_end(&mut blocks).context("invalid block data")?;
+
+    let count = (CHUNK_SIZE as usize).pow(3);
+    ensure!(blocks.len() == count * 2, "wrong block count");
+
+    // positions run in block storage order
+    let blocks = blocks.chunks_exact(2).map(|index| {
+        let index = u16::from_le_bytes([index[0], index[1]]) as usize;
+        palette.get(index).copied().context("block outside palette")
+    }).collect::<Result<ChunkBlocks>>()?;
+
+    let mut chunk = Chunk::empty(pos.0, pos.1, pos.2);
+
+    chunk.set_blocks(blocks);
+    chunk.set_decorations(decorated);
+
+    Ok(chunk)
+}
+
+// local positions in saved order
+fn chunk_positions() -> impl Iterator<Item = (usize, usize, usize)> {
+    itertools::iproduct!(0..CHUNK_SIZE as usize, 0..CHUNK_SIZE as usize, 0..CHUNK_SIZE as usize)
+        .map(|(z, x, y)| (x, y, z))
+}
+
+// read fixed bytes
+fn take<const N: usize>(reader: &mut &[u8]) -> Result<[u8; N]> {
+    ensure!(reader.len() >= N, "truncated data");
+
+    let (bytes, rest) = reader.split_at(N);
+    *reader = rest;
+
+    Ok(bytes.try_into()?)
+}
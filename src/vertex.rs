@@ -1,9 +1,9 @@
 use std::mem;
 use bytemuck::{Pod, Zeroable};
 
-// texture sizing
-pub const TEX_W: f32 = 1.0 / 6.0;
-pub const TEX_H: f32 = 1.0 / 4.0;
+// atlas tiles
+pub const ATLAS_COLS: u16 = 6;
+pub const ATLAS_ROWS: u16 = 8;
 
 // indices
 pub const INDICES: &[u16] = &[
@@ -42,34 +42,34 @@ pub fn get_vertices() -> [Vertex; 24] {
         Vertex::new([ 0.5,  0.5, 0.5], [1.0, 0.0], 0),
 
         // back face
-        Vertex::new([-0.5, -0.5, -0.5], [2.0, 1.0], 1),
-        Vertex::new([ 0.5, -0.5, -0.5], [1.0, 1.0], 1),
-        Vertex::new([-0.5,  0.5, -0.5], [2.0, 0.0], 1),
-        Vertex::new([ 0.5,  0.5, -0.5], [1.0, 0.0], 1),
+        Vertex::new([-0.5, -0.5, -0.5], [1.0, 1.0], 1),
+        Vertex::new([ 0.5, -0.5, -0.5], [0.0, 1.0], 1),
+        Vertex::new([-0.5,  0.5, -0.5], [1.0, 0.0], 1),
+        Vertex::new([ 0.5,  0.5, -0.5], [0.0, 0.0], 1),
 
         // left face
-        Vertex::new([-0.5, -0.5, -0.5], [2.0, 1.0], 2),
-        Vertex::new([-0.5, -0.5,  0.5], [3.0, 1.0], 2),
-        Vertex::new([-0.5,  0.5, -0.5], [2.0, 0.0], 2),
-        Vertex::new([-0.5,  0.5,  0.5], [3.0, 0.0], 2),
+        Vertex::new([-0.5, -0.5, -0.5], [0.0, 1.0], 2),
+        Vertex::new([-0.5, -0.5,  0.5], [1.0, 1.0], 2),
+        Vertex::new([-0.5,  0.5, -0.5], [0.0, 0.0], 2),
+        Vertex::new([-0.5,  0.5,  0.5], [1.0, 0.0], 2),
 
         // right face
-        Vertex::new([0.5, -0.5,  0.5], [3.0, 1.0], 3),
-        Vertex::new([0.5, -0.5, -0.5], [4.0, 1.0], 3),
-        Vertex::new([0.5,  0.5,  0.5], [3.0, 0.0], 3),
-        Vertex::new([0.5,  0.5, -0.5], [4.0, 0.0], 3),
+        Vertex::new([0.5, -0.5,  0.5], [0.0, 1.0], 3),
+        Vertex::new([0.5, -0.5, -0.5], [1.0, 1.0], 3),
+        Vertex::new([0.5,  0.5,  0.5], [0.0, 0.0], 3),
+        Vertex::new([0.5,  0.5, -0.5], [1.0, 0.0], 3),
 
         // top face
-        Vertex::new([-0.5, 0.5,  0.5], [4.0, 1.0], 4),
-        Vertex::new([ 0.5, 0.5,  0.5], [5.0, 1.0], 4),
-        Vertex::new([-0.5, 0.5, -0.5], [4.0, 0.0], 4),
-        Vertex::new([ 0.5, 0.5, -0.5], [5.0, 0.0], 4),
+        Vertex::new([-0.5, 0.5,  0.5], [0.0, 1.0], 4),
+        Vertex::new([ 0.5, 0.5,  0.5], [1.0, 1.0], 4),
+        Vertex::new([-0.5, 0.5, -0.5], [0.0, 0.0], 4),
+        Vertex::new([ 0.5, 0.5, -0.5], [1.0, 0.0], 4),
 
         // bottom face
-        Vertex::new([-0.5, -0.5,  0.5], [6.0, 1.0], 5),
-        Vertex::new([ 0.5, -0.5,  0.5], [5.0, 1.0], 5),
-        Vertex::new([-0.5, -0.5, -0.5], [6.0, 0.0], 5),
-        Vertex::new([ 0.5, -0.5, -0.5], [5.0, 0.0], 5),
+        Vertex::new([-0.5, -0.5,  0.5], [1.0, 1.0], 5),
+        Vertex::new([ 0.5, -0.5,  0.5], [0.0, 1.0], 5),
+        Vertex::new([-0.5, -0.5, -0.5], [1.0, 0.0], 5),
+        Vertex::new([ 0.5, -0.5, -0.5], [0.0, 0.0], 5),
     ]
 }
 
@@ -80,7 +80,7 @@ pub struct Vertex {
     // position
     pos: [f32; 4],
 
-    // texture position
+    // texture position in tile
     tex_pos: [f32; 2],
 
     // face index
@@ -96,8 +96,8 @@ impl Vertex {
     pub fn new(pos: [f32; 3], tex_pos: [f32; 2], face: u32) -> Self {
         Self {
             face,
-            pos:     [pos[0], pos[1], pos[2], 1.0],
-            tex_pos: [tex_pos[0] * TEX_W, tex_pos[1] * TEX_H],
+            tex_pos,
+            pos: [pos[0], pos[1], pos[2], 1.0],
         }
     }
 
@@ -1,100 +1,1269 @@
-use crate::chunk::{Chunk, CHUNK_SIZE};
-use crate::instance::Instance;
+use crate::aabb::Aabb;
+use crate::block::{Block, BlockRegistry};
+use crate::chunk::{self, Chunk, ChunkPos, Neighbours, CHUNK_SIZE, NEIGHBOURS};
+use crate::generator::{Generator, Caves};
+use crate::decorator::{Decorator, Decorations, Placement};
+use crate::fluid::{self, FLUID_TICK, MAX_UPDATES};
+use crate::light::Lighting;
+use crate::mesh::{ChunkMesh, SolidMesh, Mesh, Mesher};
+use crate::raycast::{self, RayHit};
+use crate::seed::WorldSeed;
+use crate::sky::WorldTime;
+use crate::storage::{self, ChunkStorage};
 
-use noise::permutationtable::PermutationTable;
+use anyhow::{Result, ensure};
+use cgmath::{Point3, Vector3};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Sender, Receiver};
+use std::sync::{Arc, Mutex};
+use std::fmt;
+use std::ops::RangeInclusive;
 use rayon::prelude::*;
 
-const CHUNKS: u8 = 8;
+#[cfg(target_arch = "wasm32")]
+use std::collections::VecDeque;
+
+// generation time per frame without threads
+#[cfg(target_arch = "wasm32")]
+const GENERATE_BUDGET: instant::Duration = instant::Duration::from_millis(4);
+
+// generated chunk with structure blocks for other chunks and highest blocks of its columns
+type Generated = (Chunk, Vec<Placement>, Vec<i32>);
+
+// vertical world bounds
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WorldHeight {
+    // lowest block
+    min_y: i32,
+
+    // above highest block
+    max_y: i32,
+}
+
+impl WorldHeight {
+    // create bounds aligned to chunks
+    pub fn new(min_y: i32, max_y: i32) -> Result<Self> {
+        let size = CHUNK_SIZE as i32;
+
+        ensure!(min_y < max_y, "world bottom {} must be below top {}", min_y, max_y);
+        ensure!(
+            min_y.rem_euclid(size) == 0 && max_y.rem_euclid(size) == 0,
+            "world bounds must be multiples of {}", size,
+        );
+
+        Ok(Self { min_y, max_y })
+    }
+
+    // lowest block
+    pub fn min_y(&self) -> i32 {
+        self.min_y
+    }
+
+    // above highest block
+    pub fn max_y(&self) -> i32 {
+        self.max_y
+    }
+
+    // check if height holds blocks
+    pub fn contains(&self, y: i32) -> bool {
+        y >= self.min_y && y < self.max_y
+    }
+
+    // chunk layers within bounds
+    pub fn layers(&self) -> RangeInclusive<i32> {
+        let size = CHUNK_SIZE as i32;
+        self.min_y / size..=self.max_y / size - 1
+    }
+}
+
+impl Default for WorldHeight {
+    // default bounds
+    fn default() -> Self {
+        Self {
+            min_y: -64,
+            max_y: 192,
+        }
+    }
+}
+
+// chunks loaded around center
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ViewDistance {
+    // chunks on each side along x and z
+    pub horizontal: u8,
+
+    // chunk layers above and below
+    pub vertical: u8,
+}
+
+impl Default for ViewDistance {
+    // default distance
+    fn default() -> Self {
+        Self {
+            horizontal: 8,
+            vertical:   3,
+        }
+    }
+}
+
+// chunk eviction policy
+#[derive(Copy, Clone, Debug)]
+pub struct Eviction {
+    // chunks kept beyond view distance
+    pub margin: u8,
+
+    // maximum loaded chunks
+    pub max_chunks: Option<usize>,
+
+    // maximum chunk memory in bytes
+    pub max_memory: Option<usize>,
+}
+
+impl Default for Eviction {
+    // default policy
+    fn default() -> Self {
+        Self {
+            margin:     2,
+            max_chunks: None,
+            max_memory: None,
+        }
+    }
+}
+
+// chunk counters
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WorldStats {
+    // currently loaded
+    pub loaded: usize,
+
+    // generated since start
+    pub generated: u64,
+
+    // evicted since start
+    pub evicted: u64,
+
+    // estimated chunk memory in bytes
+    pub memory: usize,
+
+    // waiting for generation
+    pub pending: usize,
+}
+
+impl fmt::Display for WorldStats {
+    // show counters
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "chunks: {} loaded, {} pending, {} generated, {} evicted, {} KiB",
+            self.loaded, self.pending, self.generated, self.evicted, self.memory / 1024,
+        )
+    }
+}
 
 // world instances
 pub struct World {
+    // world seed
+    seed: WorldSeed,
+
     // chunks
-    chunks: HashMap<(i32, i32), Chunk>,
+    chunks: HashMap<ChunkPos, Chunk>,
+
+    // chunk meshing
+    mesher: Mesher,
+    meshes: Vec<(ChunkPos, ChunkMesh)>,
+
+    // chunks needing instances
+    dirty: HashSet<ChunkPos>,
+
+    // chunks loaded around center
+    view: ViewDistance,
+
+    // chunk eviction
+    eviction: Eviction,
+    unloaded: Vec<ChunkPos>,
+    stats:    WorldStats,
+
+    // block types
+    registry: Arc<BlockRegistry>,
+
+    // terrain generator
+    generator: Arc<Generator>,
+
+    // structure placement, blocks loaded chunks place in neighbours
+    decorator:   Arc<Decorator>,
+    decorations: HashMap<ChunkPos, Vec<Placement>>,
+
+    // fluid positions to update and time since last step
+    fluids:     HashSet<[i32; 3]>,
+    fluid_time: f32,
+
+    // day and night clock
+    time: WorldTime,
+
+    // saved chunks
+    storage: Option<Arc<dyn ChunkStorage>>,
+
+    // evicted chunks not written yet, read before storage
+    unsaved: Arc<Mutex<HashMap<ChunkPos, Vec<u8>>>>,
 
-    // simplex hashers
-    hashers: Vec<(u8, PermutationTable)>,
+    // background write in flight
+    #[cfg(not(target_arch = "wasm32"))]
+    writing: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    write_sender: Sender<()>,
+    #[cfg(not(target_arch = "wasm32"))]
+    write_receiver: Receiver<()>,
+
+    // chunk generation
+    pending:  HashSet<ChunkPos>,
+    sender:   Sender<Generated>,
+    receiver: Receiver<Generated>,
+
+    // chunks to generate in frame
+    #[cfg(target_arch = "wasm32")]
+    queue: VecDeque<ChunkPos>,
+
+    // vertical bounds
+    height: WorldHeight,
 
     // loaded chunks
     loaded_x: i32,
+    loaded_y: i32,
     loaded_z: i32,
 }
 
 impl World {
     // create world
-    pub fn new() -> Self {
-        // create hashers
-        let hashers = [1, 2, 4, 8, 16]
-            .iter()
-            .map(|&step| (step, PermutationTable::new(rand::random())))
-            .collect::<Vec<(u8, PermutationTable)>>();
+    pub fn new(seed: WorldSeed) -> Self {
+        Self::with_registry(seed, BlockRegistry::builtin())
+            .expect("builtin blocks missing terrain blocks")
+    }
 
-        Self {
-            hashers,
-            chunks: HashMap::new(),
+    // create world with block types
+    pub fn with_registry(seed: WorldSeed, registry: BlockRegistry) -> Result<Self> {
+        let generator = Generator::new(seed, &registry)?;
+        let decorator = Decorator::new(seed, &registry, Decorations::default())?;
+        let (sender, receiver) = mpsc::channel();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let (write_sender, write_receiver) = mpsc::channel();
+
+        Ok(Self {
+            seed,
+            registry:  Arc::new(registry),
+            generator: Arc::new(generator),
+            decorator: Arc::new(decorator),
+            storage:   None,
+            chunks:    HashMap::new(),
+
+            unsaved: Arc::new(Mutex::new(HashMap::new())),
+
+            #[cfg(not(target_arch = "wasm32"))]
+            writing: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            write_sender,
+            #[cfg(not(target_arch = "wasm32"))]
+            write_receiver,
+
+            decorations: HashMap::new(),
+
+            fluids:     HashSet::new(),
+            fluid_time: 0.0,
+
+            time: WorldTime::default(),
+
+            pending: HashSet::new(),
+            sender,
+            receiver,
+
+            #[cfg(target_arch = "wasm32")]
+            queue: VecDeque::new(),
+
+            mesher: Mesher::default(),
+            meshes: Vec::new(),
+            dirty:  HashSet::new(),
+
+            view: ViewDistance::default(),
+
+            eviction: Eviction::default(),
+            unloaded: Vec::new(),
+            stats:    WorldStats::default(),
+
+            height: WorldHeight::default(),
 
             loaded_x: 0,
+            loaded_y: 0,
             loaded_z: 0,
+        })
+    }
+
+    // get seed
+    pub fn seed(&self) -> WorldSeed {
+        self.seed
+    }
+
+    // get block types
+    pub fn registry(&self) -> &BlockRegistry {
+        &self.registry
+    }
+
+    // get view distance
+    pub fn view_distance(&self) -> ViewDistance {
+        self.view
+    }
+
+    // set view distance, evicting chunks beyond it on next refresh
+    pub fn set_view_distance(&mut self, view: ViewDistance) {
+        self.view = view;
+    }
+
+    // get eviction policy
+    pub fn eviction(&self) -> Eviction {
+        self.eviction
+    }
+
+    // set eviction policy
+    pub fn set_eviction(&mut self, eviction: Eviction) {
+        self.eviction = eviction;
+    }
+
+    // get vertical bounds
+    pub fn height(&self) -> WorldHeight {
+        self.height
+    }
+
+    // change vertical bounds and drop loaded chunks
+    pub fn set_height(&mut self, height: WorldHeight) {
+        self.height = height;
+        self.unload_all();
+    }
+
+    // get carving settings
+    pub fn caves(&self) -> Caves {
+        self.generator.caves()
+    }
+
+    // change carving settings and drop loaded chunks
+    pub fn set_caves(&mut self, caves: Caves) -> Result<()> {
+        self.generator = Arc::new(Generator::with_caves(self.seed, &self.registry, caves)?);
+        self.unload_all();
+
+        Ok(())
+    }
+
+    // get decoration amounts
+    pub fn decorations(&self) -> Decorations {
+        self.decorator.decorations()
+    }
+
+    // change decoration amounts and drop loaded chunks
+    pub fn set_decorations(&mut self, decorations: Decorations) -> Result<()> {
+        self.decorator = Arc::new(Decorator::new(self.seed, &self.registry, decorations)?);
+        self.unload_all();
+
+        Ok(())
+    }
+
+    // unload every chunk and forget pending ones
+    fn unload_all(&mut self) {
+        let loaded  = self.chunks.keys().copied().collect::<Vec<_>>();
+        let removed = loaded.into_iter().filter_map(|pos| self.remove(pos)).collect();
+
+        self.store(removed);
+
+        self.pending.clear();
+        self.decorations.clear();
+        self.fluids.clear();
+        self.stats.pending = 0;
+    }
+
+    // get clock
+    pub fn time(&self) -> WorldTime {
+        self.time
+    }
+
+    // replace clock
+    pub fn set_time(&mut self, time: WorldTime) {
+        self.time = time;
+    }
+
+    // get chunk counters
+    pub fn stats(&self) -> WorldStats {
+        self.stats
+    }
+
+    // get loaded chunk
+    pub fn chunk(&self, pos_x: i32, pos_y: i32, pos_z: i32) -> Option<&Chunk> {
+        self.chunks.get(&(pos_x, pos_y, pos_z))
+    }
+
+    // chunk holding world position
+    pub fn chunk_pos(x: i32, y: i32, z: i32) -> ChunkPos {
+        let size = CHUNK_SIZE as i32;
+        (x.div_euclid(size), y.div_euclid(size), z.div_euclid(size))
+    }
+
+    // split world position into chunk and local position
+    pub fn locate(&self, x: i32, y: i32, z: i32) -> Option<(ChunkPos, [usize; 3])> {
+        let size = CHUNK_SIZE as i32;
+
+        // outside world bounds
+        if !self.height.contains(y) {
+            return None;
+        }
+
+        Some((
+            Self::chunk_pos(x, y, z),
+            [x.rem_euclid(size) as usize, y.rem_euclid(size) as usize, z.rem_euclid(size) as usize],
+        ))
+    }
+
+    // get block at world position
+    pub fn get_block(&self, x: i32, y: i32, z: i32) -> Option<Block> {
+        let (pos, [x, y, z]) = self.locate(x, y, z)?;
+        Some(self.chunks.get(&pos)?.block(x, y, z))
+    }
+
+    // sky and block light at world position
+    pub fn light(&self, x: i32, y: i32, z: i32) -> Option<[u8; 2]> {
+        let (pos, [x, y, z]) = self.locate(x, y, z)?;
+        Some(self.chunks.get(&pos)?.light(x, y, z))
+    }
+
+    // set block at world position, letting nearby fluids react
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, block: Block) -> bool {
+        if !self.change_block(x, y, z, block, true) {
+            return false;
+        }
+
+        self.queue_fluids([x, y, z]);
+        true
+    }
+
+    // queue position and its neighbours for fluid updates
+    fn queue_fluids(&mut self, pos: [i32; 3]) {
+        self.fluids.insert(pos);
+
+        for by in NEIGHBOURS {
+            self.fluids.insert(chunk::offset(pos, by));
+        }
+    }
+
+    // advance clock and fluids by elapsed seconds
+    pub fn tick(&mut self, dt: f32) {
+        self.time.advance(dt);
+        self.fluid_time += dt;
+
+        // one step per call so slow frames do not pile up work
+        if self.fluid_time >= FLUID_TICK {
+            self.fluid_time = (self.fluid_time - FLUID_TICK).min(FLUID_TICK);
+            self.step_fluids();
+        }
+    }
+
+    // update queued fluid positions once, returning changed blocks
+    pub fn step_fluids(&mut self) -> usize {
+        let mut queued = self.fluids.drain().collect::<Vec<_>>();
+        queued.sort_unstable();
+
+        // leave the rest for later steps
+        if queued.len() > MAX_UPDATES {
+            self.fluids.extend(queued.drain(MAX_UPDATES..));
+        }
+
+        // decide every change before applying any
+        let changes =
+            queued
+                .into_iter()
+                .filter_map(|pos| {
+                    let next = fluid::flow(&self.registry, pos, |[x, y, z]| self.get_block(x, y, z))?;
+                    Some((pos, next))
+                }).collect::<Vec<_>>();
+
+        for &([x, y, z], block) in &changes {
+            self.change_block(x, y, z, block, true);
+            self.queue_fluids([x, y, z]);
+        }
+
+        changes.len()
+    }
+
+    // queued fluid positions
+    pub fn fluids_pending(&self) -> usize {
+        self.fluids.len()
+    }
+
+    // set block, marking chunk for saving if edited
+    fn change_block(&mut self, x: i32, y: i32, z: i32, block: Block, edited: bool) -> bool {
+        let world_pos = [x, y, z];
+
+        let Some(((pos_x, pos_y, pos_z), [x, y, z])) = self.locate(x, y, z) else {
+            return false;
+        };
+
+        let Some(chunk) = self.chunks.get_mut(&(pos_x, pos_y, pos_z)) else {
+            return false;
+        };
+
+        if chunk.block(x, y, z) == block {
+            return true;
+        }
+
+        // storage may grow
+        let memory = chunk.memory();
+
+        if edited {
+            chunk.set(x, y, z, block);
+        } else {
+            chunk.place(x, y, z, block);
+        }
+
+        self.stats.memory = self.stats.memory - memory + chunk.memory();
+        self.dirty.insert((pos_x, pos_y, pos_z));
+
+        self.relight(|lighting| lighting.update_block(world_pos));
+
+        let last = CHUNK_SIZE as usize - 1;
+
+        // remesh touching neighbours
+        for (border, pos) in [
+            (x == 0,    (pos_x - 1, pos_y, pos_z)),
+            (x == last, (pos_x + 1, pos_y, pos_z)),
+            (y == 0,    (pos_x, pos_y - 1, pos_z)),
+            (y == last, (pos_x, pos_y + 1, pos_z)),
+            (z == 0,    (pos_x, pos_y, pos_z - 1)),
+            (z == last, (pos_x, pos_y, pos_z + 1)),
+        ] {
+            if border && self.chunks.contains_key(&pos) {
+                self.dirty.insert(pos);
+            }
+        }
+
+        true
+    }
+
+    // find first block along ray
+    pub fn raycast(
+        &self,
+        origin:       Point3<f32>,
+        direction:    Vector3<f32>,
+        max_distance: f32,
+    ) -> Option<RayHit> {
+        // fluids can be reached through
+        raycast::raycast(origin, direction, max_distance, |[x, y, z]| {
+            self.get_block(x, y, z).is_some_and(|block| !block.is_air() && self.registry.fluid(block).is_none())
+        })
+    }
+
+    // check if block at world position collides
+    pub fn is_solid(&self, x: i32, y: i32, z: i32) -> bool {
+        // floor below world
+        if y < self.height.min_y {
+            return true;
+        }
+
+        let Some((pos, [x, y, z])) = self.locate(x, y, z) else {
+            return false;
+        };
+
+        // wait for unloaded chunks
+        self.chunks.get(&pos).is_none_or(|chunk| self.registry.get(chunk.block(x, y, z)).solid)
+    }
+
+    // check if chunk holding world position is loaded
+    pub fn block_loaded(&self, x: i32, y: i32, z: i32) -> bool {
+        // nothing to load outside bounds
+        !self.height.contains(y) || self.chunks.contains_key(&Self::chunk_pos(x, y, z))
+    }
+
+    // world height above terrain
+    pub fn surface(&self, x: i32, z: i32) -> i32 {
+        self.generator.surface(x, z)
+    }
+
+    // remove hit block
+    pub fn break_block(&mut self, hit: &RayHit) -> bool {
+        let [x, y, z] = hit.pos;
+        self.set_block(x, y, z, Block::AIR)
+    }
+
+    // place block on hit face
+    pub fn place_block(&mut self, hit: &RayHit, block: Block, player: &Aabb) -> bool {
+        // no face when starting inside block
+        if hit.normal == [0; 3] {
+            return false;
+        }
+
+        let [x, y, z] = hit.adjacent();
+
+        // only replace air and fluids
+        let replaceable = self.get_block(x, y, z).is_some_and(|block| {
+            block.is_air() || self.registry.fluid(block).is_some()
+        });
+
+        if !replaceable {
+            return false;
         }
+
+        // keep solid blocks out of player
+        if self.registry.get(block).solid && Aabb::block([x, y, z]).intersects(player) {
+            return false;
+        }
+
+        self.set_block(x, y, z, block)
     }
 
     // check if refresh is required
-    pub fn refresh_required(&self, pos_x: i32, pos_z: i32) -> bool {
-        let chunk_x = pos_x / CHUNK_SIZE as i32;
-        let chunk_z = pos_z / CHUNK_SIZE as i32;
+    pub fn refresh_required(&self, pos_x: i32, pos_y: i32, pos_z: i32) -> bool {
+        let (chunk_x, chunk_y, chunk_z) = Self::chunk_pos(pos_x, pos_y, pos_z);
+
+        // recenter before leaving small views
+        let reach  = (self.view.horizontal as i32).min(3);
+        let layers = (self.view.vertical as i32).min(1);
 
         // in loaded chunks
-        chunk_x < self.loaded_x - 3 ||
-           chunk_x > self.loaded_x + 3 ||
-           chunk_z < self.loaded_z - 3 ||
-           chunk_z > self.loaded_z + 3
+        chunk_x < self.loaded_x - reach ||
+           chunk_x > self.loaded_x + reach ||
+           chunk_y < self.loaded_y - layers ||
+           chunk_y > self.loaded_y + layers ||
+           chunk_z < self.loaded_z - reach ||
+           chunk_z > self.loaded_z + reach
     }
 
-    // load chunk
-    pub fn load(&mut self, pos_x: i32, pos_z: i32) {
-        let chunk = Chunk::new(pos_x, pos_z, &self.hashers);
-        self.chunks.insert((pos_x, pos_z), chunk);
+    // get neighbouring chunks
+    pub fn neighbours(&self, pos_x: i32, pos_y: i32, pos_z: i32) -> Neighbours<'_> {
+        Self::neighbours_in(&self.chunks, (pos_x, pos_y, pos_z))
     }
 
-    // get instances
-    pub fn instances(&mut self, pos_x: i32, pos_z: i32) -> Vec<Instance> {
-        // movement direction
-        let x_dir = ((pos_x / CHUNK_SIZE as i32) - self.loaded_x).signum();
-        let z_dir = ((pos_z / CHUNK_SIZE as i32) - self.loaded_z).signum();
+    // get neighbouring chunks from map
+    fn neighbours_in(chunks: &HashMap<ChunkPos, Chunk>, (x, y, z): ChunkPos) -> Neighbours<'_> {
+        Neighbours {
+            front: chunks.get(&(x, y, z + 1)),
+            back:  chunks.get(&(x, y, z - 1)),
 
-        // chunks center
-        let chunk_x = self.loaded_x + x_dir;
-        let chunk_z = self.loaded_z + z_dir;
-
-        self.loaded_x = chunk_x;
-        self.loaded_z = chunk_z;
-
-        let chunks =
-            itertools::iproduct!(
-                (chunk_x - CHUNKS as i32..=chunk_x + CHUNKS as i32),
-                (chunk_z - CHUNKS as i32..=chunk_z + CHUNKS as i32)
-            );
-
-        for (x, z) in chunks.clone() {
-            // load if not available
-            if !self.chunks.contains_key(&(x, z)) {
-                self.load(x, z);
+            left:  chunks.get(&(x - 1, y, z)),
+            right: chunks.get(&(x + 1, y, z)),
+
+            top:    chunks.get(&(x, y + 1, z)),
+            bottom: chunks.get(&(x, y - 1, z)),
+        }
+    }
+
+    // face neighbour positions
+    fn adjacent((x, y, z): ChunkPos) -> impl Iterator<Item = ChunkPos> {
+        NEIGHBOURS.into_iter().map(move |[dx, dy, dz]| (x + dx, y + dy, z + dz))
+    }
+
+    // offsets of every neighbour sharing a face, edge or corner
+    fn around() -> impl Iterator<Item = ChunkPos> {
+        itertools::iproduct!(-1..=1, -1..=1, -1..=1).filter(|&offset| offset != (0, 0, 0))
+    }
+
+    // load chunk immediately
+    pub fn load(&mut self, pos_x: i32, pos_y: i32, pos_z: i32) {
+        let pos = (pos_x, pos_y, pos_z);
+        self.pending.remove(&pos);
+
+        let generated = Self::restore(pos, &self.generator, &self.decorator, &self.registry, self.storage.as_deref(), &self.unsaved);
+        self.insert(generated);
+    }
+
+    // load saved chunk or generate new one
+    fn restore(
+        pos:       ChunkPos,
+        generator: &Generator,
+        decorator: &Decorator,
+        registry:  &BlockRegistry,
+        storage:   Option<&dyn ChunkStorage>,
+        unsaved:   &Mutex<HashMap<ChunkPos, Vec<u8>>>,
+    ) -> Generated {
+        let saved = storage.map(|storage| {
+            // evicted chunks still being written are newer than storage
+            let evicted = unsaved.lock().unwrap().get(&pos).cloned();
+
+            let data = match evicted {
+                Some(data) => Some(data),
+                None       => storage.read(pos)?,
+            };
+
+            data.map(|data| storage::decode_chunk(pos, &data, registry)).transpose()
+        });
+
+        match saved {
+            // saved chunks hold their structures, neighbours may still lack theirs
+            Some(Ok(Some(chunk))) => {
+                let tops       = generator.tops(pos);
+                let placements = decorator.placements(pos, generator, &tops);
+
+                (chunk, placements, tops)
+            }
+
+            // regenerate unreadable chunks
+            Some(Err(err)) => {
+                log::warn!("failed to load chunk {:?}: {:#}", pos, err);
+                Self::generate_chunk(pos, generator, decorator)
             }
+
+            _ => Self::generate_chunk(pos, generator, decorator),
+        }
+    }
+
+    // generate and decorate chunk
+    fn generate_chunk(pos: ChunkPos, generator: &Generator, decorator: &Decorator) -> Generated {
+        let (mut chunk, tops) = Chunk::generated(pos.0, pos.1, pos.2, generator);
+        let placements        = decorator.decorate(&mut chunk, generator);
+
+        (chunk, placements, tops)
+    }
+
+    // set saved chunk backend
+    pub fn set_storage(&mut self, storage: Arc<dyn ChunkStorage>) {
+        self.storage = Some(storage);
+    }
+
+    // saved chunk backend
+    pub fn storage(&self) -> Option<&dyn ChunkStorage> {
+        self.storage.as_deref()
+    }
+
+    // save chunk if changed
+    fn save_chunk(storage: Option<&dyn ChunkStorage>, registry: &BlockRegistry, chunk: &mut Chunk) -> Result<()> {
+        let Some(storage) = storage else {
+            return Ok(());
+        };
+
+        if chunk.modified() {
+            storage.write(chunk.pos(), &storage::encode_chunk(chunk, registry)?)?;
+            chunk.mark_saved();
+        }
+
+        Ok(())
+    }
+
+    // save changed and evicted chunks
+    pub fn save(&mut self) -> Result<()> {
+        self.settle();
+
+        let Some(storage) = self.storage.as_deref() else {
+            return Ok(());
+        };
+
+        let mut changed = self.chunks.values_mut().filter(|chunk| chunk.modified()).collect::<Vec<_>>();
+
+        // loaded chunks are newer than their evicted copies
+        let mut saved = self.unsaved.lock().unwrap().drain().collect::<HashMap<_, _>>();
+
+        for chunk in &changed {
+            saved.insert(chunk.pos(), storage::encode_chunk(chunk, &self.registry)?);
+        }
+
+        // one write per region
+        storage.write_chunks(&saved.into_iter().collect::<Vec<_>>())?;
+
+        for chunk in &mut changed {
+            // edits may leave unused palette entries
+            let memory = chunk.memory();
+            chunk.mark_saved();
+
+            self.stats.memory = self.stats.memory - memory + chunk.memory();
+        }
+
+        Ok(())
+    }
+
+    // insert generated chunk
+    fn insert(&mut self, (mut chunk, placements, tops): Generated) {
+        let pos = chunk.pos();
+
+        // structures of loaded neighbours not placed yet, saved with chunk
+        for (dx, dy, dz) in Self::around() {
+            let source = (pos.0 + dx, pos.1 + dy, pos.2 + dz);
+
+            let Some(placements) = self.decorations.get(&source) else {
+                continue;
+            };
+
+            if chunk.decorated((dx, dy, dz)) {
+                continue;
+            }
+
+            for placement in placements {
+                let [x, y, z] = placement.pos;
+
+                if Self::chunk_pos(x, y, z) != pos {
+                    continue;
+                }
+
+                let [x, y, z] = placement.pos.map(|coord| coord.rem_euclid(CHUNK_SIZE as i32) as usize);
+
+                if chunk.block(x, y, z) == placement.replaces {
+                    chunk.set(x, y, z, placement.block);
+                }
+            }
+
+            chunk.set_decorated((dx, dy, dz));
+        }
+
+        self.stats.generated += 1;
+        self.stats.memory    += chunk.memory();
+
+        if let Some(old) = self.chunks.insert(pos, chunk) {
+            self.stats.memory -= old.memory();
         }
 
-        // get instances
-        chunks
-            .par_bridge()
-            .flat_map(|(x, z)| {
-                self.chunks.get(&(x, z)).unwrap().instances()
-            }).collect()
+        self.stats.loaded = self.chunks.len();
+
+        // cull chunk and neighbour borders
+        self.dirty.insert(pos);
+        self.dirty.extend(Self::adjacent(pos));
+
+        self.relight(|lighting| lighting.light_chunk(pos, &tops));
+
+        // structures reaching into loaded neighbours, kept for ones loading later
+        self.decorations.insert(pos, placements);
+
+        for (dx, dy, dz) in Self::around() {
+            self.decorate(pos, (pos.0 + dx, pos.1 + dy, pos.2 + dz));
+        }
+    }
+
+    // propagate light, remeshing chunks it changes
+    fn relight(&mut self, update: impl FnOnce(&mut Lighting)) {
+        let mut lighting = Lighting::new(&mut self.chunks, &self.registry, &self.generator, self.height.max_y);
+        update(&mut lighting);
+
+        self.stats.memory += lighting.grown;
+
+        for pos in lighting.touched {
+            if self.chunks.contains_key(&pos) {
+                self.dirty.insert(pos);
+            }
+        }
+    }
+
+    // place structures of source chunk in loaded target chunk once
+    fn decorate(&mut self, source: ChunkPos, target: ChunkPos) {
+        let offset = (source.0 - target.0, source.1 - target.1, source.2 - target.2);
+
+        let Some(chunk) = self.chunks.get_mut(&target) else {
+            return;
+        };
+
+        if chunk.decorated(offset) {
+            return;
+        }
+
+        chunk.set_decorated(offset);
+
+        let placements =
+            self.decorations
+                .get(&source)
+                .into_iter()
+                .flatten()
+                .filter(|placement| Self::chunk_pos(placement.pos[0], placement.pos[1], placement.pos[2]) == target)
+                .copied()
+                .collect::<Vec<_>>();
+
+        for Placement { pos: [x, y, z], block, replaces } in placements {
+            if self.get_block(x, y, z) == Some(replaces) {
+                self.change_block(x, y, z, block, true);
+            }
+        }
+    }
+
+    // request missing chunks in view
+    fn request(&mut self, pos_x: i32, pos_y: i32, pos_z: i32) {
+        let center = Self::chunk_pos(pos_x, pos_y, pos_z);
+
+        let mut missing =
+            self.visible()
+                .filter(|pos| !self.chunks.contains_key(pos) && !self.pending.contains(pos))
+                .collect::<Vec<_>>();
+
+        // nearest chunks first
+        missing.sort_by_key(|&(x, y, z)| (x - center.0).pow(2) + (y - center.1).pow(2) + (z - center.2).pow(2));
+
+        for pos in missing {
+            self.pending.insert(pos);
+            self.dispatch(pos);
+        }
+
+        self.stats.pending = self.pending.len();
+    }
+
+    // generate chunk on worker pool
+    #[cfg(not(target_arch = "wasm32"))]
+    fn dispatch(&mut self, pos: ChunkPos) {
+        let sender    = self.sender.clone();
+        let generator = self.generator.clone();
+        let decorator = self.decorator.clone();
+        let registry  = self.registry.clone();
+        let storage   = self.storage.clone();
+        let unsaved   = self.unsaved.clone();
+
+        rayon::spawn(move || {
+            let generated = Self::restore(pos, &generator, &decorator, &registry, storage.as_deref(), &unsaved);
+
+            // world may be dropped before completion
+            let _ = sender.send(generated);
+        });
+    }
+
+    // queue chunk for frame budget
+    #[cfg(target_arch = "wasm32")]
+    fn dispatch(&mut self, pos: ChunkPos) {
+        self.queue.push_back(pos);
+    }
+
+    // generate queued chunks within budget
+    #[cfg(target_arch = "wasm32")]
+    fn generate(&mut self, budget: Option<instant::Duration>) {
+        let start = instant::Instant::now();
+
+        while budget.is_none_or(|budget| start.elapsed() < budget) {
+            let Some(pos) = self.queue.pop_front() else {
+                break;
+            };
+
+            // skip cancelled requests
+            if self.pending.contains(&pos) {
+                let generated = Self::restore(pos, &self.generator, &self.decorator, &self.registry, self.storage.as_deref(), &self.unsaved);
+                let _ = self.sender.send(generated);
+            }
+        }
+    }
+
+    // integrate finished chunks
+    fn integrate(&mut self) {
+        #[cfg(target_arch = "wasm32")]
+        self.generate(Some(GENERATE_BUDGET));
+
+        while let Ok(generated) = self.receiver.try_recv() {
+            self.complete(generated);
+        }
+    }
+
+    // accept requested chunk
+    fn complete(&mut self, generated: Generated) {
+        // drop chunks no longer requested
+        if self.pending.remove(&generated.0.pos()) {
+            self.insert(generated);
+        }
+
+        self.stats.pending = self.pending.len();
+    }
+
+    // wait for pending chunks
+    pub fn finish(&mut self) {
+        #[cfg(target_arch = "wasm32")]
+        self.generate(None);
+
+        while !self.pending.is_empty() {
+            match self.receiver.recv() {
+                Ok(generated) => self.complete(generated),
+                Err(_)    => break,
+            }
+        }
+    }
+
+    // unload chunk, saving it immediately
+    pub fn unload(&mut self, pos_x: i32, pos_y: i32, pos_z: i32) {
+        let pos = (pos_x, pos_y, pos_z);
+
+        let Some(mut chunk) = self.remove(pos) else {
+            return;
+        };
+
+        // older evicted copy must not overwrite it
+        self.settle();
+        self.unsaved.lock().unwrap().remove(&pos);
+
+        // keep changes on disk
+        if let Err(err) = Self::save_chunk(self.storage.as_deref(), &self.registry, &mut chunk) {
+            log::warn!("failed to save chunk {:?}: {:#}", pos, err);
+        }
+    }
+
+    // remove chunk, returning it for saving
+    fn remove(&mut self, pos: ChunkPos) -> Option<Chunk> {
+        let chunk = self.chunks.remove(&pos)?;
+
+        self.stats.evicted += 1;
+        self.stats.memory  -= chunk.memory();
+        self.stats.loaded   = self.chunks.len();
+
+        // drop pending work, neighbours loading later regain structures once it reloads
+        self.decorations.remove(&pos);
+        self.dirty.remove(&pos);
+        self.meshes.retain(|(meshed, _)| *meshed != pos);
+
+        // expose neighbour borders
+        for next in Self::adjacent(pos) {
+            if self.chunks.contains_key(&next) {
+                self.dirty.insert(next);
+            }
+        }
+
+        self.unloaded.push(pos);
+
+        Some(chunk)
+    }
+
+    // queue changed chunks and write them together
+    fn store(&mut self, chunks: Vec<Chunk>) {
+        if self.storage.is_none() {
+            return;
+        }
+
+        let mut unsaved = self.unsaved.lock().unwrap();
+
+        for chunk in chunks.iter().filter(|chunk| chunk.modified()) {
+            match storage::encode_chunk(chunk, &self.registry) {
+                Ok(data) => {
+                    unsaved.insert(chunk.pos(), data);
+                }
+
+                Err(err) => log::warn!("failed to save chunk {:?}: {:#}", chunk.pos(), err),
+            }
+        }
+
+        drop(unsaved);
+        self.flush();
+    }
+
+    // write queued chunks on worker pool, one batch at a time
+    #[cfg(not(target_arch = "wasm32"))]
+    fn flush(&mut self) {
+        // previous batch finished
+        if self.write_receiver.try_recv().is_ok() {
+            self.writing = false;
+        }
+
+        if self.writing {
+            return;
+        }
+
+        let Some(storage) = self.storage.clone() else {
+            return;
+        };
+
+        let batch = self.unsaved.lock().unwrap().iter().map(|(&pos, data)| (pos, data.clone())).collect::<Vec<_>>();
+
+        if batch.is_empty() {
+            return;
+        }
+
+        let unsaved = self.unsaved.clone();
+        let sender  = self.write_sender.clone();
+
+        self.writing = true;
+
+        rayon::spawn(move || {
+            if let Err(err) = storage.write_chunks(&batch) {
+                log::warn!("failed to save chunks: {:#}", err);
+            }
+
+            // keep chunks evicted again meanwhile
+            let mut unsaved = unsaved.lock().unwrap();
+
+            for (pos, data) in batch {
+                if unsaved.get(&pos) == Some(&data) {
+                    unsaved.remove(&pos);
+                }
+            }
+
+            drop(unsaved);
+
+            // world may be dropped before completion
+            let _ = sender.send(());
+        });
+    }
+
+    // write queued chunks, storage finishes in background
+    #[cfg(target_arch = "wasm32")]
+    fn flush(&mut self) {
+        let Some(storage) = self.storage.as_deref() else {
+            return;
+        };
+
+        let batch = self.unsaved.lock().unwrap().drain().collect::<Vec<_>>();
+
+        if batch.is_empty() {
+            return;
+        }
+
+        if let Err(err) = storage.write_chunks(&batch) {
+            log::warn!("failed to save chunks: {:#}", err);
+        }
+    }
+
+    // wait for background write
+    #[cfg(not(target_arch = "wasm32"))]
+    fn settle(&mut self) {
+        if self.writing {
+            let _ = self.write_receiver.recv();
+            self.writing = false;
+        }
+    }
+
+    // writes are queued by storage
+    #[cfg(target_arch = "wasm32")]
+    fn settle(&mut self) {}
+
+    // take unloaded chunk positions
+    pub fn take_unloaded(&mut self) -> Vec<ChunkPos> {
+        std::mem::take(&mut self.unloaded)
+    }
+
+    // evict chunks by distance and limits
+    fn evict(&mut self) {
+        let center = (self.loaded_x, self.loaded_y, self.loaded_z);
+        let margin = self.eviction.margin as i32;
+        let view   = self.view.horizontal as i32;
+        let layers = self.view.vertical as i32;
+
+        // distance from loaded center, scaled to horizontal view
+        let distance = |&(x, y, z): &ChunkPos| {
+            let vertical = (y - center.1).abs() - layers + view;
+            (x - center.0).abs().max((z - center.2).abs()).max(vertical)
+        };
+
+        // chunks outside range
+        let range = view + margin;
+
+        let far =
+            self.chunks
+                .keys()
+                .copied()
+                .filter(|pos| distance(pos) > range)
+                .collect::<Vec<_>>();
+
+        let mut evicted = far.into_iter().filter_map(|pos| self.remove(pos)).collect::<Vec<_>>();
+
+        // cancel requests outside range
+        self.pending.retain(|pos| distance(pos) <= range);
+        self.stats.pending = self.pending.len();
+
+        // check limits
+        let over_limit = |world: &Self| {
+            world.eviction.max_chunks.is_some_and(|max| world.stats.loaded > max) ||
+                world.eviction.max_memory.is_some_and(|max| world.stats.memory > max)
+        };
+
+        if over_limit(self) {
+            // farthest chunks outside view first
+            let mut candidates =
+                self.chunks
+                    .keys()
+                    .copied()
+                    .filter(|pos| distance(pos) > view)
+                    .collect::<Vec<_>>();
+
+            candidates.sort_by_key(distance);
+
+            while over_limit(self) {
+                let Some(pos) = candidates.pop() else {
+                    break;
+                };
+
+                evicted.extend(self.remove(pos));
+            }
+        }
+
+        // one write for every evicted chunk
+        self.store(evicted);
+    }
+
+    // get mesher
+    pub fn mesher(&self) -> Mesher {
+        self.mesher
+    }
+
+    // change mesher and rebuild chunks
+    pub fn set_mesher(&mut self, mesher: Mesher) {
+        self.mesher = mesher;
+        self.dirty.extend(self.chunks.keys().copied());
+    }
+
+    // chunks in view
+    pub fn visible(&self) -> impl Iterator<Item = ChunkPos> {
+        let layers = self.height.layers();
+        let view   = self.view.horizontal as i32;
+
+        // keep layers within world bounds
+        let bottom = (self.loaded_y - self.view.vertical as i32).max(*layers.start());
+        let top    = (self.loaded_y + self.view.vertical as i32).min(*layers.end());
+
+        itertools::iproduct!(
+            (self.loaded_x - view..=self.loaded_x + view),
+            (bottom..=top),
+            (self.loaded_z - view..=self.loaded_z + view)
+        )
+    }
+
+    // load and mesh chunks around position
+    pub fn refresh(&mut self, pos_x: i32, pos_y: i32, pos_z: i32) {
+        if self.refresh_required(pos_x, pos_y, pos_z) {
+            self.recenter(pos_x, pos_y, pos_z);
+        }
+
+        // generate in background
+        self.request(pos_x, pos_y, pos_z);
+        self.integrate();
+
+        self.evict();
+
+        let chunks   = &self.chunks;
+        let mesher   = self.mesher;
+        let registry = &*self.registry;
+
+        // rebuild dirty meshes
+        let rebuilt =
+            self.dirty
+                .par_iter()
+                .filter_map(|pos| {
+                    let chunk = chunks.get(pos)?;
+                    let neighbours = Self::neighbours_in(chunks, *pos);
+
+                    let solid = match mesher {
+                        Mesher::Instanced => SolidMesh::Instanced(chunk.instances(&neighbours, registry)),
+                        Mesher::Greedy    => SolidMesh::Greedy(Mesh::greedy(chunk, &neighbours, registry)),
+                    };
+
+                    let translucent = Mesh::translucent(chunk, &neighbours, registry);
+
+                    Some((*pos, ChunkMesh { solid, translucent }))
+                }).collect::<Vec<_>>();
+
+        self.meshes.extend(rebuilt);
+        self.dirty.clear();
+    }
+
+    // take rebuilt chunk meshes
+    pub fn take_meshes(&mut self) -> Vec<(ChunkPos, ChunkMesh)> {
+        std::mem::take(&mut self.meshes)
+    }
+
+    // move loaded chunks towards position
+    fn recenter(&mut self, pos_x: i32, pos_y: i32, pos_z: i32) {
+        let (chunk_x, chunk_y, chunk_z) = Self::chunk_pos(pos_x, pos_y, pos_z);
+
+        // movement direction
+        let x_dir = (chunk_x - self.loaded_x).signum();
+        let y_dir = (chunk_y - self.loaded_y).signum();
+        let z_dir = (chunk_z - self.loaded_z).signum();
+
+        // chunks center
+        self.loaded_x += x_dir;
+        self.loaded_y += y_dir;
+        self.loaded_z += z_dir;
     }
 }
 
 impl Default for World {
     // default world
     fn default() -> Self {
-        Self::new()
+        Self::new(WorldSeed::random())
     }
 }
@@ -0,0 +1,87 @@
+use blocks::biome::Biome;
+use blocks::block::{Block, BlockRegistry};
+use blocks::generator::{Generator, Caves, SEA_LEVEL};
+use blocks::seed::WorldSeed;
+
+use std::collections::HashSet;
+
+mod common;
+
+use common::generator;
+
+#[test]
+fn every_biome_appears() {
+    let generator = generator(1);
+
+    let biomes =
+        itertools::iproduct!((-4096..4096).step_by(64), (-4096..4096).step_by(64))
+            .map(|(x, z)| generator.biome(x, z))
+            .collect::<HashSet<_>>();
+
+    assert_eq!(biomes.len(), Biome::ALL.len());
+}
+
+#[test]
+fn borders_blend_smoothly() {
+    let generator = generator(2);
+    let mut borders = 0;
+
+    // walk lines crossing many biomes
+    for z in [0, 1000, -2500] {
+        for x in -4096..4096 {
+            let (a, b) = (generator.column(x, z), generator.column(x + 1, z));
+
+            if a.biome != b.biome {
+                borders += 1;
+            }
+
+            // no cliffs where curves differ
+            assert!((a.height - b.height).abs() <= 8, "cliff at ({}, {})", x, z);
+        }
+    }
+
+    assert!(borders > 0);
+}
+
+#[test]
+fn surface_matches_biome() {
+    let registry  = BlockRegistry::builtin();
+    let generator = Generator::with_caves(WorldSeed::new(3), &registry, Caves::NONE).unwrap();
+
+    for (x, z) in itertools::iproduct!((-2048..2048).step_by(97), (-2048..2048).step_by(97)) {
+        let column  = generator.column(x, z);
+        let surface = registry.get(generator.block_at(x, column.height, z)).name.as_str();
+
+        // high peaks are snowy, shores and sea floors sandy
+        if column.height >= 48 {
+            assert_eq!(surface, "snow");
+        } else if column.height <= SEA_LEVEL + 1 {
+            assert_eq!(surface, "sand");
+        } else {
+            assert_eq!(surface, column.biome.layers().surface);
+        }
+
+        // covered by sea below sea level
+        let above = if column.height < SEA_LEVEL { registry.id("water").unwrap() } else { Block::AIR };
+        assert_eq!(generator.block_at(x, column.height + 1, z), above);
+        assert_eq!(generator.block_at(x, column.height - 10, z), registry.id("stone").unwrap());
+    }
+}
+
+#[test]
+fn biomes_follow_seed() {
+    let (a, b) = (generator(7), generator(7));
+
+    assert!(
+        itertools::iproduct!((-1024..1024).step_by(31), (-1024..1024).step_by(31))
+            .all(|(x, z)| a.biome(x, z) == b.biome(x, z) && a.height(x, z) == b.height(x, z))
+    );
+
+    // other seeds lay out climate differently
+    let other = generator(8);
+
+    assert!(
+        itertools::iproduct!((-1024..1024).step_by(31), (-1024..1024).step_by(31))
+            .any(|(x, z)| a.biome(x, z) != other.biome(x, z))
+    );
+}
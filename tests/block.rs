@@ -0,0 +1,147 @@
+use blocks::block::{Block, BlockDef, BlockRegistry, Fluid, Textures, FLUID_LEVELS};
+use blocks::vertex::ATLAS_COLS;
+
+#[test]
+fn air_is_first() {
+    let registry = BlockRegistry::new();
+
+    assert_eq!(registry.id("air"), Some(Block::AIR));
+    assert!(registry.get(Block::AIR).transparent);
+    assert!(!registry.get(Block::AIR).solid);
+}
+
+#[test]
+fn builtin_terrain_blocks() {
+    let registry = BlockRegistry::builtin();
+
+    for name in ["dirt", "grass"] {
+        let block = registry.require(name).unwrap();
+
+        assert!(!block.is_air());
+        assert_eq!(registry.get(block).name, name);
+        assert!(registry.get(block).solid);
+    }
+}
+
+#[test]
+fn register_assigns_ids() {
+    let mut registry = BlockRegistry::new();
+
+    let stone = registry.register(BlockDef::new("stone", Textures::all([0, 0]))).unwrap();
+    let glass = registry.register(BlockDef {
+        transparent: true,
+        ..BlockDef::new("glass", Textures::all([1, 0]))
+    }).unwrap();
+
+    assert_eq!(stone, Block(1));
+    assert_eq!(glass, Block(2));
+
+    assert_eq!(registry.id("glass"), Some(glass));
+    assert!(registry.get(glass).transparent);
+    assert_eq!(registry.iter().count(), 3);
+}
+
+#[test]
+fn rejects_duplicates() {
+    let mut registry = BlockRegistry::builtin();
+
+    assert!(registry.register(BlockDef::new("dirt", Textures::all([0, 0]))).is_err());
+    assert!(registry.register(BlockDef::new("air", Textures::all([0, 0]))).is_err());
+}
+
+#[test]
+fn parses_json_defaults() {
+    let registry = BlockRegistry::from_json(r#"[
+        { "name": "lamp", "textures": { "all": [1, 0] }, "emissive": 14, "hardness": 0.3 },
+        { "name": "glass", "textures": { "all": [2, 0] }, "transparent": true },
+        { "name": "ghost", "textures": { "all": [3, 0] }, "solid": false }
+    ]"#).unwrap();
+
+    let lamp = registry.get(registry.require("lamp").unwrap());
+    assert_eq!(lamp.emissive, 14);
+    assert_eq!(lamp.tiles(), [1; 6]);
+    assert!(lamp.solid && !lamp.transparent);
+
+    let glass = registry.get(registry.require("glass").unwrap());
+    assert!(glass.transparent);
+    assert_eq!(glass.hardness, 1.0);
+
+    assert!(!registry.get(registry.require("ghost").unwrap()).solid);
+}
+
+#[test]
+fn rejects_invalid_json() {
+    let textures = r#""textures": { "all": [0, 0] }"#;
+
+    assert!(BlockRegistry::from_json(&format!(r#"[{{ {} }}]"#, textures)).is_err());
+    assert!(BlockRegistry::from_json(&format!(r#"[{{ "name": "x", "colour": 1, {} }}]"#, textures)).is_err());
+    assert!(BlockRegistry::from_json(&format!(r#"[{{ "name": "x", "emissive": 16, {} }}]"#, textures)).is_err());
+}
+
+#[test]
+fn rejects_missing_textures() {
+    // bottom has no tile
+    assert!(BlockRegistry::from_json(r#"[
+        { "name": "x", "textures": { "side": [0, 0], "top": [1, 0] } }
+    ]"#).is_err());
+
+    // outside atlas
+    assert!(BlockRegistry::from_json(r#"[
+        { "name": "x", "textures": { "all": [99, 0] } }
+    ]"#).is_err());
+}
+
+#[test]
+fn resolves_face_tiles() {
+    let textures = Textures {
+        all:   Some([0, 0]),
+        side:  Some([1, 0]),
+        front: Some([2, 0]),
+        top:   Some([3, 1]),
+        ..Default::default()
+    };
+
+    let tiles = BlockDef::new("x", textures).tiles();
+
+    // front, back, left, right, top, bottom
+    assert_eq!(tiles, [2, 1, 1, 1, ATLAS_COLS + 3, 0]);
+}
+
+#[test]
+fn grass_faces_differ() {
+    let registry = BlockRegistry::builtin();
+    let tiles = registry.get(registry.require("grass").unwrap()).tiles();
+
+    assert_eq!(tiles[0], tiles[3]);
+    assert_ne!(tiles[0], tiles[4]);
+    assert_ne!(tiles[4], tiles[5]);
+}
+
+#[test]
+fn fluids_register_levels() {
+    let registry = BlockRegistry::builtin();
+    let water    = registry.require("water").unwrap();
+
+    assert_eq!(registry.fluid(water), Some(Fluid { source: water, level: FLUID_LEVELS }));
+    assert!(registry.fluid(registry.require("stone").unwrap()).is_none());
+
+    // flowing levels are named and looked up by level
+    for level in 1..FLUID_LEVELS {
+        let block = registry.require(&format!("water_{}", level)).unwrap();
+
+        assert_eq!(registry.fluid(block), Some(Fluid { source: water, level }));
+        assert_eq!(registry.fluid_block(water, level), block);
+        assert!(registry.get(block).transparent && !registry.get(block).solid);
+    }
+
+    assert_eq!(registry.fluid_block(water, FLUID_LEVELS), water);
+}
+
+#[test]
+fn blended_blocks() {
+    let registry = BlockRegistry::builtin();
+    let blended  = |name| registry.get(registry.require(name).unwrap()).blended();
+
+    assert!(blended("water") && blended("water_3") && blended("glass") && blended("leaves"));
+    assert!(!blended("stone") && !blended("air"));
+}
@@ -0,0 +1,102 @@
+use blocks::block::{Block, BlockRegistry};
+use blocks::chunk::{Chunk, CHUNK_SIZE};
+use blocks::generator::{Generator, Caves};
+use blocks::seed::WorldSeed;
+use blocks::world::World;
+
+// blocks along chunk edge
+const SIZE: i32 = CHUNK_SIZE as i32;
+
+// generator for carving settings
+fn generator(caves: Caves) -> Generator {
+    Generator::with_caves(WorldSeed::new(4), &BlockRegistry::builtin(), caves).unwrap()
+}
+
+// air below the surface of chunk
+fn hollow(chunk: &Chunk, generator: &Generator) -> usize {
+    let [off_x, off_y, off_z] = chunk.origin().map(|off| off as i32);
+
+    itertools::iproduct!(0..SIZE, 0..SIZE, 0..SIZE)
+        .filter(|&(x, y, z)| y + off_y < generator.height(x + off_x, z + off_z) - 8)
+        .filter(|&(x, y, z)| chunk.block(x as usize, y as usize, z as usize) == Block::AIR)
+        .count()
+}
+
+#[test]
+fn carves_below_surface() {
+    let carved = generator(Caves::default());
+    let flat   = generator(Caves::NONE);
+
+    let chunks = itertools::iproduct!(-2..2, -3..0, -2..2).collect::<Vec<_>>();
+
+    assert!(chunks.iter().map(|&(x, y, z)| hollow(&Chunk::new(x, y, z, &carved), &carved)).sum::<usize>() > 0);
+    assert!(chunks.iter().all(|&(x, y, z)| hollow(&Chunk::new(x, y, z, &flat), &flat) == 0));
+}
+
+#[test]
+fn thresholds_control_carving() {
+    let chunks = itertools::iproduct!(-2..2, -3..0, -2..2).collect::<Vec<_>>();
+
+    let hollow_with = |caves| {
+        let generator = generator(caves);
+        chunks.iter().map(|&(x, y, z)| hollow(&Chunk::new(x, y, z, &generator), &generator)).sum::<usize>()
+    };
+
+    // lower threshold and wider tunnels carve more
+    let default = hollow_with(Caves::default());
+    let wide    = hollow_with(Caves { threshold: 0.6, tunnels: 0.2, ..Default::default() });
+
+    assert!(wide > default);
+}
+
+#[test]
+fn overhangs_leave_heightmap() {
+    let generator = generator(Caves { threshold: 1.0, tunnels: 0.0, overhangs: 6.0 });
+
+    // blocks above column height or air below it
+    let shifted =
+        itertools::iproduct!(-64..64, -64..64)
+            .filter(|&(x, z)| {
+                let height = generator.height(x, z);
+                generator.block_at(x, height + 3, z) != Block::AIR || generator.block_at(x, height - 3, z) == Block::AIR
+            }).count();
+
+    assert!(shifted > 0);
+}
+
+#[test]
+fn chunks_match_world_positions() {
+    let generator = generator(Caves::default());
+
+    // every block depends only on its world position
+    for (pos_x, pos_y, pos_z) in itertools::iproduct!(-1..1, [-1], -1..1) {
+        let chunk = Chunk::new(pos_x, pos_y, pos_z, &generator);
+
+        for (x, y, z) in itertools::iproduct!(0..SIZE, 0..SIZE, 0..SIZE) {
+            assert_eq!(
+                chunk.block(x as usize, y as usize, z as usize),
+                generator.block_at(x + pos_x * SIZE, y + pos_y * SIZE, z + pos_z * SIZE),
+                "chunk ({}, {}, {}) differs at ({}, {}, {})", pos_x, pos_y, pos_z, x, y, z,
+            );
+        }
+    }
+}
+
+#[test]
+fn caves_continue_across_borders() {
+    let mut world = World::new(WorldSeed::new(4));
+
+    for pos in itertools::iproduct!(-2..2, -3..0, -2..2) {
+        world.load(pos.0, pos.1, pos.2);
+    }
+
+    // air on both sides of a chunk border
+    let crossings =
+        itertools::iproduct!(-32..32, -48..0, [-SIZE, 0, SIZE])
+            .filter(|&(x, y, z)| {
+                world.get_block(x, y, z - 1) == Some(Block::AIR) && world.get_block(x, y, z) == Some(Block::AIR)
+            }).filter(|&(x, y, z)| y < world.surface(x, z) - 12)
+            .count();
+
+    assert!(crossings > 0);
+}
@@ -0,0 +1,99 @@
+use blocks::block::Block;
+use blocks::chunk_blocks::ChunkBlocks;
+
+// blocks per chunk
+const LEN: usize = 4096;
+
+#[test]
+fn uniform_without_indices() {
+    let blocks = ChunkBlocks::uniform(LEN, Block(3));
+
+    assert_eq!(blocks.single(), Some(Block(3)));
+    assert_eq!(blocks.bits(), 0);
+    assert_eq!(blocks.get(LEN - 1), Block(3));
+    assert!(blocks.memory() < 16);
+}
+
+#[test]
+fn grows_bits_with_palette() {
+    let mut blocks = ChunkBlocks::uniform(LEN, Block::AIR);
+
+    blocks.set(7, Block(1));
+    assert_eq!(blocks.bits(), 1);
+
+    blocks.set(8, Block(2));
+    assert_eq!(blocks.bits(), 2);
+
+    for id in 3..20 {
+        blocks.set(id as usize * 100, Block(id));
+    }
+
+    assert_eq!(blocks.bits(), 8);
+    assert_eq!(blocks.single(), None);
+
+    // earlier blocks survive repacking
+    assert_eq!(blocks.get(7), Block(1));
+    assert_eq!(blocks.get(8), Block(2));
+    assert_eq!(blocks.get(1900), Block(19));
+    assert_eq!(blocks.get(0), Block::AIR);
+}
+
+#[test]
+fn matches_flat_array() {
+    let mut blocks   = ChunkBlocks::uniform(LEN, Block::AIR);
+    let mut expected = vec![Block::AIR; LEN];
+
+    // pseudo random writes over many types
+    let mut state = 12345u32;
+
+    for _ in 0..20000 {
+        state = state.wrapping_mul(1103515245).wrapping_add(12345);
+
+        let index = (state >> 8) as usize % LEN;
+        let block = Block((state >> 20) as u16 % 300);
+
+        blocks.set(index, block);
+        expected[index] = block;
+    }
+
+    assert_eq!(blocks.bits(), 16);
+    assert!(blocks.iter().eq(expected.iter().copied()));
+}
+
+#[test]
+fn collects_packed() {
+    let blocks = (0..LEN).map(|index| Block((index % 5) as u16)).collect::<ChunkBlocks>();
+
+    assert_eq!(blocks.len(), LEN);
+    assert_eq!(blocks.palette().len(), 5);
+    assert_eq!(blocks.bits(), 4);
+    assert_eq!(blocks.get(13), Block(3));
+
+    // indices take a quarter of a flat array
+    assert!(blocks.memory() <= LEN * std::mem::size_of::<Block>() / 4 + 64);
+
+    // single type collects uniform
+    let uniform = std::iter::repeat_n(Block(2), LEN).collect::<ChunkBlocks>();
+    assert_eq!(uniform.single(), Some(Block(2)));
+}
+
+#[test]
+fn compact_drops_unused() {
+    let mut blocks = ChunkBlocks::uniform(LEN, Block::AIR);
+
+    blocks.set(0, Block(1));
+    blocks.set(1, Block(2));
+    blocks.set(2, Block(3));
+
+    // overwrite back to air
+    for index in 0..3 {
+        blocks.set(index, Block::AIR);
+    }
+
+    assert_eq!(blocks.palette().len(), 4);
+
+    blocks.compact();
+
+    assert_eq!(blocks.single(), Some(Block::AIR));
+    assert_eq!(blocks.palette(), &[Block::AIR]);
+}
@@ -0,0 +1,44 @@
+// fixtures shared by test files, each using only some of them
+#![allow(dead_code)]
+
+use blocks::block::BlockRegistry;
+use blocks::chunk::{Chunk, CHUNK_SIZE};
+use blocks::generator::Generator;
+use blocks::seed::WorldSeed;
+use blocks::world::World;
+
+use std::fs;
+use std::path::PathBuf;
+
+// floor height of test area, far above terrain
+pub const FLOOR: i32 = 130;
+
+// world with loaded sky chunks around the origin
+pub fn sky() -> World {
+    let mut world = World::new(WorldSeed::new(1));
+
+    for (x, z) in itertools::iproduct!(-1..=1, -1..=1) {
+        world.load(x, 8, z);
+    }
+
+    world
+}
+
+// generator for seed
+pub fn generator(seed: u64) -> Generator {
+    Generator::new(WorldSeed::new(seed), &BlockRegistry::builtin()).unwrap()
+}
+
+// compare every block of two chunks
+pub fn same_blocks(a: &Chunk, b: &Chunk) -> bool {
+    itertools::iproduct!(0..CHUNK_SIZE as usize, 0..CHUNK_SIZE as usize, 0..CHUNK_SIZE as usize)
+        .all(|(x, y, z)| a.block(x, y, z) == b.block(x, y, z))
+}
+
+// empty directory for test
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("blocks-{}-{}", name, std::process::id()));
+    let _   = fs::remove_dir_all(&dir);
+
+    dir
+}
@@ -0,0 +1,246 @@
+use blocks::block::BlockRegistry;
+use blocks::chunk::{Chunk, ChunkPos, CHUNK_SIZE};
+use blocks::decorator::{Decorator, Decorations, Placement};
+use blocks::generator::Generator;
+use blocks::region::RegionStorage;
+use blocks::seed::WorldSeed;
+use blocks::world::World;
+
+use std::fs;
+use std::sync::Arc;
+
+mod common;
+
+use common::{same_blocks, temp_dir};
+
+// blocks along chunk edge
+const SIZE: i32 = CHUNK_SIZE as i32;
+
+// generator and decorator for seed
+fn stages(seed: u64, decorations: Decorations) -> (Generator, Decorator) {
+    let registry = BlockRegistry::builtin();
+
+    (
+        Generator::new(WorldSeed::new(seed), &registry).unwrap(),
+        Decorator::new(WorldSeed::new(seed), &registry, decorations).unwrap(),
+    )
+}
+
+// every block position of chunk
+fn positions() -> impl Iterator<Item = (usize, usize, usize)> {
+    itertools::iproduct!(0..SIZE as usize, 0..SIZE as usize, 0..SIZE as usize)
+}
+
+// chunks around surface near origin
+fn surface_chunks() -> impl Iterator<Item = ChunkPos> {
+    itertools::iproduct!(-3..3, -1..3, -3..3)
+}
+
+// structure block of some chunk landing in air or stone of another chunk
+fn crossing_placement() -> (ChunkPos, Placement) {
+    let (generator, decorator) = stages(6, Decorations::default());
+
+    surface_chunks()
+        .flat_map(|pos| {
+            let mut chunk = Chunk::new(pos.0, pos.1, pos.2, &generator);
+            decorator.decorate(&mut chunk, &generator).into_iter().map(move |placement| (pos, placement))
+        }).find(|(_, placement)| generator.block_at(placement.pos[0], placement.pos[1], placement.pos[2]) == placement.replaces)
+        .unwrap()
+}
+
+#[test]
+fn same_seed_same_structures() {
+    let (generator, decorator) = stages(6, Decorations::default());
+
+    for pos in surface_chunks().take(24) {
+        let mut a = Chunk::new(pos.0, pos.1, pos.2, &generator);
+        let mut b = Chunk::new(pos.0, pos.1, pos.2, &generator);
+
+        assert_eq!(decorator.decorate(&mut a, &generator), decorator.decorate(&mut b, &generator));
+        assert!(same_blocks(&a, &b));
+
+        // generated structures need no saving
+        assert!(!a.modified());
+    }
+}
+
+#[test]
+fn placements_without_chunk() {
+    let (generator, decorator) = stages(6, Decorations::default());
+
+    // saved chunks recover structures for neighbours from terrain alone
+    for pos in surface_chunks() {
+        let (mut chunk, tops) = Chunk::generated(pos.0, pos.1, pos.2, &generator);
+
+        assert_eq!(generator.tops(pos), tops);
+        assert_eq!(decorator.placements(pos, &generator, &tops), decorator.decorate(&mut chunk, &generator));
+    }
+}
+
+#[test]
+fn places_trees_and_ores() {
+    let registry               = BlockRegistry::builtin();
+    let (generator, decorator) = stages(6, Decorations::default());
+
+    let mut counts = [0; 3];
+
+    for pos in itertools::iproduct!(-3..3, -3..3, -3..3) {
+        let mut chunk = Chunk::new(pos.0, pos.1, pos.2, &generator);
+        decorator.decorate(&mut chunk, &generator);
+
+        for (x, y, z) in positions() {
+            match registry.get(chunk.block(x, y, z)).name.as_str() {
+                "log"                              => counts[0] += 1,
+                "leaves"                           => counts[1] += 1,
+                "coal_ore" | "iron_ore" | "gold_ore" => counts[2] += 1,
+                _ => (),
+            }
+        }
+    }
+
+    assert!(counts.iter().all(|&count| count > 0), "counts {:?}", counts);
+}
+
+#[test]
+fn ores_only_replace_stone() {
+    let registry               = BlockRegistry::builtin();
+    let (generator, decorator) = stages(6, Decorations { ores: 4.0, ..Decorations::NONE });
+    let stone                  = registry.id("stone").unwrap();
+
+    for pos in itertools::iproduct!(-1..1, -3..0, -1..1) {
+        let plain     = Chunk::new(pos.0, pos.1, pos.2, &generator);
+        let mut chunk = Chunk::new(pos.0, pos.1, pos.2, &generator);
+
+        decorator.decorate(&mut chunk, &generator);
+
+        // changed blocks were stone
+        assert!(
+            positions()
+                .filter(|&(x, y, z)| chunk.block(x, y, z) != plain.block(x, y, z))
+                .all(|(x, y, z)| plain.block(x, y, z) == stone)
+        );
+    }
+}
+
+#[test]
+fn no_decorations() {
+    let (generator, decorator) = stages(6, Decorations::NONE);
+
+    for pos in surface_chunks().take(24) {
+        let plain     = Chunk::new(pos.0, pos.1, pos.2, &generator);
+        let mut chunk = Chunk::new(pos.0, pos.1, pos.2, &generator);
+
+        assert!(decorator.decorate(&mut chunk, &generator).is_empty());
+        assert!(same_blocks(&chunk, &plain));
+    }
+}
+
+#[test]
+fn structures_cross_borders() {
+    let (generator, decorator) = stages(6, Decorations::default());
+
+    // some structure reaches outside its chunk
+    let crossing = surface_chunks().find(|pos| {
+        let mut chunk = Chunk::new(pos.0, pos.1, pos.2, &generator);
+        !decorator.decorate(&mut chunk, &generator).is_empty()
+    });
+
+    assert!(crossing.is_some());
+}
+
+#[test]
+fn load_order_does_not_matter() {
+    let chunks = surface_chunks().collect::<Vec<_>>();
+
+    // neighbours before and after their structures
+    let mut forward  = World::new(WorldSeed::new(6));
+    let mut backward = World::new(WorldSeed::new(6));
+
+    for &(x, y, z) in &chunks {
+        forward.load(x, y, z);
+    }
+
+    for &(x, y, z) in chunks.iter().rev() {
+        backward.load(x, y, z);
+    }
+
+    for (x, y, z) in chunks {
+        let (a, b) = (forward.chunk(x, y, z).unwrap(), backward.chunk(x, y, z).unwrap());
+
+        assert!(same_blocks(a, b), "chunk ({}, {}, {}) differs", x, y, z);
+
+        // chunks holding neighbour structures are saved either way
+        assert_eq!(a.modified(), b.modified());
+    }
+}
+
+#[test]
+fn queues_structures_for_missing_chunks() {
+    let mut world        = World::new(WorldSeed::new(6));
+    let (pos, placement) = crossing_placement();
+
+    let [x, y, z] = placement.pos;
+    let target    = World::chunk_pos(x, y, z);
+
+    // structure first, target later
+    world.load(pos.0, pos.1, pos.2);
+    assert!(world.get_block(x, y, z).is_none());
+
+    world.load(target.0, target.1, target.2);
+    assert_ne!(world.get_block(x, y, z), Some(placement.replaces));
+}
+
+#[test]
+fn reloaded_chunks_regain_structures() {
+    let mut world           = World::new(WorldSeed::new(6));
+    let (source, placement) = crossing_placement();
+
+    let [x, y, z] = placement.pos;
+    let target    = World::chunk_pos(x, y, z);
+
+    world.load(source.0, source.1, source.2);
+    world.load(target.0, target.1, target.2);
+
+    // neighbour structures are saved with the chunk
+    assert!(world.chunk(target.0, target.1, target.2).unwrap().modified());
+
+    // evicted next to its loaded source
+    world.unload(target.0, target.1, target.2);
+    world.load(target.0, target.1, target.2);
+
+    assert_ne!(world.get_block(x, y, z), Some(placement.replaces));
+}
+
+#[test]
+fn removed_structures_stay_removed() {
+    let dir                 = temp_dir("decorations");
+    let (source, placement) = crossing_placement();
+
+    let [x, y, z] = placement.pos;
+    let target    = World::chunk_pos(x, y, z);
+
+    // world with storage, loading chunks in order
+    let open = |order: [ChunkPos; 2]| {
+        let mut world = World::new(WorldSeed::new(6));
+        world.set_storage(Arc::new(RegionStorage::open(&dir).unwrap()));
+
+        for (pos_x, pos_y, pos_z) in order {
+            world.load(pos_x, pos_y, pos_z);
+        }
+
+        world
+    };
+
+    // break structure block next to its source
+    let mut world = open([source, target]);
+    assert!(world.set_block(x, y, z, placement.replaces));
+
+    world.save().unwrap();
+
+    // neither load order places it again
+    for order in [[source, target], [target, source]] {
+        assert_eq!(open(order).get_block(x, y, z), Some(placement.replaces));
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,204 @@
+use blocks::block::{Block, BlockRegistry, FLUID_LEVELS};
+use blocks::chunk::{Chunk, Neighbours};
+use blocks::generator::{Generator, Caves, SEA_LEVEL};
+use blocks::mesh::Mesh;
+use blocks::seed::WorldSeed;
+use blocks::world::World;
+
+mod common;
+
+use common::{sky, FLOOR};
+
+// stone floor spanning radius around the origin
+fn floor(world: &mut World, radius: i32) {
+    let stone = world.registry().id("stone").unwrap();
+
+    for (x, z) in itertools::iproduct!(-radius..=radius, -radius..=radius) {
+        assert!(world.set_block(x, FLOOR, z, stone));
+    }
+}
+
+// step fluids until they settle
+fn settle(world: &mut World) {
+    for _ in 0..100 {
+        world.step_fluids();
+
+        if world.fluids_pending() == 0 {
+            return;
+        }
+    }
+
+    panic!("fluids did not settle");
+}
+
+// fluid level at world position
+fn level(world: &World, x: i32, y: i32, z: i32) -> Option<u8> {
+    world.registry().fluid(world.get_block(x, y, z)?).map(|fluid| fluid.level)
+}
+
+#[test]
+fn sources_spread_on_floor() {
+    let mut world = sky();
+    floor(&mut world, 12);
+
+    let water = world.registry().id("water").unwrap();
+    world.set_block(0, FLOOR + 1, 0, water);
+    settle(&mut world);
+
+    assert_eq!(level(&world, 0, FLOOR + 1, 0), Some(FLUID_LEVELS));
+
+    // one level lost per block travelled
+    assert_eq!(level(&world, 1, FLOOR + 1, 0),  Some(FLUID_LEVELS - 1));
+    assert_eq!(level(&world, 0, FLOOR + 1, -3), Some(FLUID_LEVELS - 3));
+    assert_eq!(level(&world, 2, FLOOR + 1, 2),  Some(FLUID_LEVELS - 4));
+    assert_eq!(level(&world, -7, FLOOR + 1, 0), Some(1));
+
+    assert_eq!(world.get_block(8, FLOOR + 1, 0), Some(Block::AIR));
+    assert_eq!(world.get_block(4, FLOOR + 1, 4), Some(Block::AIR));
+
+    // nothing climbs
+    assert_eq!(world.get_block(0, FLOOR + 2, 0), Some(Block::AIR));
+}
+
+#[test]
+fn fluids_fall_before_spreading() {
+    let mut world = sky();
+    floor(&mut world, 12);
+
+    let water = world.registry().id("water").unwrap();
+    world.set_block(0, FLOOR + 5, 0, water);
+    settle(&mut world);
+
+    // column below the source falls nearly full
+    for y in FLOOR + 1..FLOOR + 5 {
+        assert_eq!(level(&world, 0, y, 0), Some(FLUID_LEVELS - 1));
+    }
+
+    // then spreads along the floor from the columns beside it
+    assert_eq!(level(&world, 3, FLOOR + 1, 0), Some(FLUID_LEVELS - 3));
+
+    // falling fluid does not spread in midair
+    assert_eq!(world.get_block(0, FLOOR + 3, 2), Some(Block::AIR));
+}
+
+#[test]
+fn removed_sources_drain() {
+    let mut world = sky();
+    floor(&mut world, 12);
+
+    let water = world.registry().id("water").unwrap();
+    world.set_block(0, FLOOR + 1, 0, water);
+    settle(&mut world);
+
+    world.set_block(0, FLOOR + 1, 0, Block::AIR);
+    settle(&mut world);
+
+    assert!(
+        itertools::iproduct!(-9..=9, -9..=9)
+            .all(|(x, z)| world.get_block(x, FLOOR + 1, z) == Some(Block::AIR))
+    );
+}
+
+#[test]
+fn blocks_stop_fluids() {
+    let mut world = sky();
+    floor(&mut world, 12);
+
+    let stone = world.registry().id("stone").unwrap();
+    let water = world.registry().id("water").unwrap();
+
+    // wall on one side
+    for z in -12..=12 {
+        world.set_block(2, FLOOR + 1, z, stone);
+    }
+
+    world.set_block(0, FLOOR + 1, 0, water);
+    settle(&mut world);
+
+    assert_eq!(world.get_block(2, FLOOR + 1, 0), Some(stone));
+    assert_eq!(world.get_block(3, FLOOR + 1, 0), Some(Block::AIR));
+
+    // flowing around the far end would take too long
+    assert_eq!(level(&world, -3, FLOOR + 1, 0), Some(FLUID_LEVELS - 3));
+}
+
+#[test]
+fn ticks_wait_for_interval() {
+    let mut world = sky();
+    floor(&mut world, 4);
+
+    let water = world.registry().id("water").unwrap();
+    world.set_block(0, FLOOR + 1, 0, water);
+
+    world.tick(0.1);
+    assert_eq!(world.get_block(1, FLOOR + 1, 0), Some(Block::AIR));
+
+    world.tick(0.2);
+    assert_eq!(level(&world, 1, FLOOR + 1, 0), Some(FLUID_LEVELS - 1));
+}
+
+#[test]
+fn sea_fills_low_terrain() {
+    let registry  = BlockRegistry::builtin();
+    let generator = Generator::with_caves(WorldSeed::new(3), &registry, Caves::NONE).unwrap();
+    let water     = registry.id("water").unwrap();
+
+    let mut seas = 0;
+
+    for (x, z) in itertools::iproduct!((-2048..2048).step_by(97), (-2048..2048).step_by(97)) {
+        let height = generator.height(x, z);
+
+        // flooded up to sea level, never above
+        if height < SEA_LEVEL {
+            assert_eq!(generator.block_at(x, SEA_LEVEL, z), water);
+            assert_eq!(generator.block_at(x, height + 1, z), water);
+            seas += 1;
+        }
+
+        assert_eq!(generator.block_at(x, SEA_LEVEL.max(height) + 1, z), Block::AIR);
+    }
+
+    assert!(seas > 0);
+}
+
+#[test]
+fn fluid_mesh_lowers_surfaces() {
+    let registry = BlockRegistry::builtin();
+    let water    = registry.id("water").unwrap();
+    let stone    = registry.id("stone").unwrap();
+
+    let mut chunk = Chunk::empty(0, 0, 0);
+    chunk.set(4, 4, 4, registry.fluid_block(water, 4));
+    chunk.set(8, 4, 4, stone);
+
+    // fluids stay out of opaque meshes
+    assert_eq!(Mesh::greedy(&chunk, &Neighbours::default(), &registry).quads(), 6);
+    assert_eq!(chunk.instances(&Neighbours::default(), &registry).len(), 1);
+
+    let mesh = Mesh::translucent(&chunk, &Neighbours::default(), &registry);
+    assert_eq!(mesh.quads(), 6);
+
+    let top = mesh.vertices.iter().map(|vertex| vertex.pos()[1]).fold(f32::MIN, f32::max);
+    assert_eq!(top, 4.0 + 4.0 / (FLUID_LEVELS + 1) as f32);
+}
+
+#[test]
+fn fluid_mesh_hides_shared_faces() {
+    let registry = BlockRegistry::builtin();
+    let water    = registry.id("water").unwrap();
+
+    let mut chunk = Chunk::empty(0, 0, 0);
+    chunk.set(4, 4, 4, water);
+    chunk.set(5, 4, 4, water);
+
+    assert_eq!(Mesh::translucent(&chunk, &Neighbours::default(), &registry).quads(), 10);
+
+    // lower level shows the step down from the source
+    chunk.set(5, 4, 4, registry.fluid_block(water, 2));
+    assert_eq!(Mesh::translucent(&chunk, &Neighbours::default(), &registry).quads(), 11);
+
+    // fluid above fills the block below
+    chunk.set(5, 4, 4, water);
+    chunk.set(4, 5, 4, water);
+    assert_eq!(Mesh::translucent(&chunk, &Neighbours::default(), &registry).quads(), 15);
+}
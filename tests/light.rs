@@ -0,0 +1,147 @@
+use blocks::block::Block;
+use blocks::light::MAX_LIGHT;
+use blocks::seed::WorldSeed;
+use blocks::world::World;
+
+mod common;
+
+use common::{sky, FLOOR};
+
+// fill box between corners with block
+fn fill(world: &mut World, min: [i32; 3], max: [i32; 3], block: Block) {
+    for (x, y, z) in itertools::iproduct!(min[0]..=max[0], min[1]..=max[1], min[2]..=max[2]) {
+        assert!(world.set_block(x, y, z, block));
+    }
+}
+
+// sky light at world position
+fn sky_light(world: &World, x: i32, y: i32, z: i32) -> u8 {
+    world.light(x, y, z).unwrap()[0]
+}
+
+// block light at world position
+fn block_light(world: &World, x: i32, y: i32, z: i32) -> u8 {
+    world.light(x, y, z).unwrap()[1]
+}
+
+#[test]
+fn open_air_is_fully_lit() {
+    let world = sky();
+
+    assert_eq!(world.light(0, FLOOR, 0), Some([MAX_LIGHT, 0]));
+    assert_eq!(world.light(-16, 128, 31), Some([MAX_LIGHT, 0]));
+
+    // nothing outside loaded chunks
+    assert_eq!(world.light(0, 200, 0), None);
+}
+
+#[test]
+fn roofs_cast_shadows() {
+    let mut world = sky();
+    let stone     = world.registry().id("stone").unwrap();
+
+    fill(&mut world, [-12, FLOOR, -12], [12, FLOOR, 12], stone);
+    fill(&mut world, [-3, FLOOR + 4, -3], [3, FLOOR + 4, 3], stone);
+
+    // full sky falls straight down beside the roof
+    assert_eq!(sky_light(&world, 4, FLOOR + 1, 0), MAX_LIGHT);
+
+    // under the roof light fades with distance from its edge
+    assert_eq!(sky_light(&world, 3, FLOOR + 1, 0), MAX_LIGHT - 1);
+    assert_eq!(sky_light(&world, 0, FLOOR + 1, 0), MAX_LIGHT - 4);
+    assert_eq!(sky_light(&world, 0, FLOOR + 3, 0), MAX_LIGHT - 4);
+
+    // opaque blocks hold no light
+    assert_eq!(sky_light(&world, 0, FLOOR + 4, 0), 0);
+    assert_eq!(sky_light(&world, 0, FLOOR, 0), 0);
+
+    // opening the roof lets the sky back in
+    assert!(world.set_block(0, FLOOR + 4, 0, Block::AIR));
+
+    assert_eq!(sky_light(&world, 0, FLOOR + 4, 0), MAX_LIGHT);
+    assert_eq!(sky_light(&world, 0, FLOOR + 1, 0), MAX_LIGHT);
+    assert_eq!(sky_light(&world, 1, FLOOR + 1, 0), MAX_LIGHT - 1);
+
+    // closing it again restores the shadow
+    assert!(world.set_block(0, FLOOR + 4, 0, stone));
+
+    assert_eq!(sky_light(&world, 0, FLOOR + 1, 0), MAX_LIGHT - 4);
+    assert_eq!(sky_light(&world, 1, FLOOR + 1, 0), MAX_LIGHT - 3);
+}
+
+#[test]
+fn translucent_blocks_dim_sky() {
+    let mut world = sky();
+    let glass     = world.registry().id("glass").unwrap();
+
+    assert!(world.set_block(0, FLOOR + 4, 0, glass));
+
+    // glass passes light on without keeping the column at full light
+    assert_eq!(sky_light(&world, 0, FLOOR + 4, 0), MAX_LIGHT - 1);
+    assert_eq!(sky_light(&world, 0, FLOOR + 3, 0), MAX_LIGHT - 1);
+}
+
+#[test]
+fn emissive_blocks_light_surroundings() {
+    let mut world = sky();
+    let glowstone = world.registry().id("glowstone").unwrap();
+    let stone     = world.registry().id("stone").unwrap();
+
+    assert!(world.set_block(0, FLOOR, 0, glowstone));
+
+    // one level lost per block travelled
+    assert_eq!(block_light(&world, 0, FLOOR, 0), MAX_LIGHT);
+    assert_eq!(block_light(&world, 0, FLOOR + 1, 0), MAX_LIGHT - 1);
+    assert_eq!(block_light(&world, 3, FLOOR, 0), MAX_LIGHT - 3);
+    assert_eq!(block_light(&world, 2, FLOOR - 2, 1), MAX_LIGHT - 5);
+    assert_eq!(block_light(&world, 14, FLOOR, 0), 1);
+    assert_eq!(block_light(&world, 15, FLOOR, 0), 0);
+
+    // walls make light go around them
+    fill(&mut world, [2, FLOOR - 2, -3], [2, FLOOR + 3, 3], stone);
+
+    assert_eq!(block_light(&world, 2, FLOOR, 0), 0);
+    assert_eq!(block_light(&world, 3, FLOOR, 0), MAX_LIGHT - 11);
+
+    // removed sources take their light with them
+    assert!(world.set_block(0, FLOOR, 0, Block::AIR));
+
+    assert_eq!(block_light(&world, 0, FLOOR, 0), 0);
+    assert_eq!(block_light(&world, 1, FLOOR, 0), 0);
+    assert_eq!(block_light(&world, 3, FLOOR, 0), 0);
+
+    // sky light is untouched
+    assert_eq!(sky_light(&world, 1, FLOOR, 0), MAX_LIGHT);
+}
+
+#[test]
+fn light_crosses_chunk_borders() {
+    let mut world = sky();
+    let glowstone = world.registry().id("glowstone").unwrap();
+
+    assert!(world.set_block(14, FLOOR, 0, glowstone));
+
+    assert_eq!(block_light(&world, 16, FLOOR, 0), MAX_LIGHT - 2);
+    assert_eq!(block_light(&world, 20, FLOOR, 0), MAX_LIGHT - 6);
+}
+
+#[test]
+fn load_order_does_not_change_light() {
+    let load = |layers: Vec<i32>| {
+        let mut world = World::new(WorldSeed::new(1));
+
+        for (y, x, z) in itertools::iproduct!(layers, -1..=1, -1..=1) {
+            world.load(x, y, z);
+        }
+
+        world
+    };
+
+    let down = load((-1..=3).rev().collect());
+    let up   = load((-1..=3).collect());
+
+    // compare the middle column, which has all of its neighbours
+    for (x, y, z) in itertools::iproduct!(0..16, -16..64, 0..16) {
+        assert_eq!(down.light(x, y, z), up.light(x, y, z), "light at {:?}", (x, y, z));
+    }
+}
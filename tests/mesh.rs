@@ -0,0 +1,387 @@
+use blocks::block::{Block, BlockRegistry};
+use blocks::chunk::{Chunk, Neighbours, CHUNK_SIZE};
+use blocks::generator::Generator;
+use blocks::light::Channel;
+use blocks::mesh::{self, Mesh};
+use blocks::seed::WorldSeed;
+
+// builtin block
+fn block(name: &str) -> Block {
+    BlockRegistry::builtin().id(name).unwrap()
+}
+
+// greedy mesh with builtin blocks
+fn greedy(chunk: &Chunk, neighbours: &Neighbours) -> Mesh {
+    Mesh::greedy(chunk, neighbours, &BlockRegistry::builtin())
+}
+
+// fill box of blocks
+fn fill(chunk: &mut Chunk, min: [usize; 3], max: [usize; 3], block: Block) {
+    for (x, y, z) in itertools::iproduct!(min[0]..max[0], min[1]..max[1], min[2]..max[2]) {
+        chunk.set(x, y, z, block);
+    }
+}
+
+// full layer of blocks
+fn layer(pos_x: i32, pos_z: i32) -> Chunk {
+    let mut chunk = Chunk::empty(pos_x, 0, pos_z);
+    fill(&mut chunk, [0, 10, 0], [CHUNK_SIZE as usize, 11, CHUNK_SIZE as usize], block("dirt"));
+
+    chunk
+}
+
+#[test]
+fn empty_chunk() {
+    let mesh = greedy(&Chunk::empty(0, 0, 0), &Neighbours::default());
+
+    assert_eq!(mesh.quads(), 0);
+    assert!(mesh.indices.is_empty());
+}
+
+#[test]
+fn single_block() {
+    let mut chunk = Chunk::empty(0, 0, 0);
+    chunk.set(4, 10, 4, block("dirt"));
+
+    let mesh = greedy(&chunk, &Neighbours::default());
+
+    assert_eq!(mesh.quads(), 6);
+    assert_eq!(mesh.indices.len(), 36);
+}
+
+#[test]
+fn merged_box() {
+    let mut chunk = Chunk::empty(0, 0, 0);
+    fill(&mut chunk, [2, 5, 3], [6, 9, 10], block("dirt"));
+
+    assert_eq!(greedy(&chunk, &Neighbours::default()).quads(), 6);
+}
+
+#[test]
+fn different_blocks_split() {
+    let mut chunk = Chunk::empty(0, 0, 0);
+    chunk.set(0, 0, 0, block("dirt"));
+    chunk.set(1, 0, 0, block("grass"));
+
+    // shared faces merge only along the left and right ends
+    assert_eq!(greedy(&chunk, &Neighbours::default()).quads(), 10);
+}
+
+#[test]
+fn l_shape() {
+    let mut chunk = Chunk::empty(0, 0, 0);
+    chunk.set(0, 0, 0, block("dirt"));
+    chunk.set(1, 0, 0, block("dirt"));
+    chunk.set(0, 1, 0, block("dirt"));
+
+    // front, back, right and top faces split into two quads each
+    assert_eq!(greedy(&chunk, &Neighbours::default()).quads(), 10);
+}
+
+#[test]
+fn hidden_faces_culled() {
+    let mut chunk = Chunk::empty(0, 0, 0);
+    fill(&mut chunk, [0, 0, 0], [3, 3, 3], block("dirt"));
+
+    // remove center block, leaving a sealed cavity
+    chunk.set(1, 1, 1, Block::AIR);
+
+    assert_eq!(greedy(&chunk, &Neighbours::default()).quads(), 12);
+}
+
+#[test]
+fn layer_without_neighbours() {
+    let chunk = layer(0, 0);
+
+    // top, bottom and four exposed borders
+    assert_eq!(greedy(&chunk, &Neighbours::default()).quads(), 6);
+}
+
+#[test]
+fn layer_with_neighbours() {
+    let chunk = layer(0, 0);
+
+    let (front, back) = (layer(0, 1), layer(0, -1));
+    let (left, right) = (layer(-1, 0), layer(1, 0));
+
+    let neighbours = Neighbours {
+        front: Some(&front),
+        back:  Some(&back),
+        left:  Some(&left),
+        right: Some(&right),
+
+        ..Default::default()
+    };
+
+    // borders culled against neighbours
+    assert_eq!(greedy(&chunk, &neighbours).quads(), 2);
+}
+
+#[test]
+fn border_faces_follow_neighbours() {
+    let registry = BlockRegistry::builtin();
+    let size     = CHUNK_SIZE as i32;
+
+    // block on right border, neighbour solid or open across it
+    let mut chunk = Chunk::empty(0, 0, 0);
+    let mut solid = Chunk::empty(1, 0, 0);
+    let open      = Chunk::empty(1, 0, 0);
+
+    chunk.set(size as usize - 1, 4, 4, block("dirt"));
+    solid.set(0, 4, 4, block("dirt"));
+
+    let beside = |right| Neighbours { right, ..Default::default() };
+
+    // unloaded neighbours read as air
+    assert_eq!(chunk.block_at(size, 4, 4, &beside(None)), Block::AIR);
+    assert_eq!(chunk.block_at(size, 4, 4, &beside(Some(&solid))), block("dirt"));
+
+    // right face hidden only by a solid neighbour
+    let right_face = |right| chunk.instances(&beside(right), &registry)[0].faces() & 1 << 3 != 0;
+
+    assert!(right_face(None));
+    assert!(right_face(Some(&open)));
+    assert!(!right_face(Some(&solid)));
+
+    // greedy meshes cull the same border
+    assert_eq!(greedy(&chunk, &beside(None)).quads(), 6);
+    assert_eq!(greedy(&chunk, &beside(Some(&solid))).quads(), 5);
+}
+
+#[test]
+fn solid_with_vertical_neighbours() {
+    let size = CHUNK_SIZE as usize;
+
+    // full chunk of dirt
+    let solid = |pos_y| {
+        let mut chunk = Chunk::empty(0, pos_y, 0);
+        fill(&mut chunk, [0; 3], [size; 3], block("dirt"));
+
+        chunk
+    };
+
+    let (chunk, top, bottom) = (solid(0), solid(1), solid(-1));
+
+    let neighbours = Neighbours {
+        top:    Some(&top),
+        bottom: Some(&bottom),
+
+        ..Default::default()
+    };
+
+    // only the four sides stay exposed
+    assert_eq!(greedy(&chunk, &Neighbours::default()).quads(), 6);
+    assert_eq!(greedy(&chunk, &neighbours).quads(), 4);
+}
+
+#[test]
+fn sky_chunk_is_empty() {
+    let registry  = BlockRegistry::builtin();
+    let generator = Generator::new(WorldSeed::new(9), &registry).unwrap();
+
+    // far above terrain
+    let chunk = Chunk::new(0, 8, 0, &generator);
+
+    assert!(chunk.is_empty());
+    assert!(chunk.instances(&Neighbours::default(), &registry).is_empty());
+    assert_eq!(greedy(&chunk, &Neighbours::default()).quads(), 0);
+
+    // filled below
+    assert!(!Chunk::new(0, -2, 0, &generator).is_empty());
+}
+
+#[test]
+fn generated_chunk_matches_faces() {
+    let registry   = BlockRegistry::builtin();
+    let generator  = Generator::new(WorldSeed::new(9), &registry).unwrap();
+
+    let chunk      = Chunk::new(0, 0, 0, &generator);
+    let neighbours = Neighbours::default();
+
+    // visible faces in instances
+    let faces =
+        chunk
+            .instances(&neighbours, &registry)
+            .iter()
+            .map(|instance| instance.faces().count_ones() as usize)
+            .sum::<usize>();
+
+    let quads = Mesh::greedy(&chunk, &neighbours, &registry).quads();
+
+    assert!(quads > 0);
+    assert!(quads < faces);
+}
+
+#[test]
+fn translucent_blocks_mesh_separately() {
+    let registry  = BlockRegistry::builtin();
+    let mut chunk = Chunk::empty(0, 0, 0);
+
+    chunk.set(4, 4, 4, block("glass"));
+    chunk.set(5, 4, 4, block("glass"));
+    chunk.set(4, 4, 5, block("stone"));
+
+    // stone keeps its face towards the glass
+    assert_eq!(greedy(&chunk, &Neighbours::default()).quads(), 6);
+    assert_eq!(chunk.instances(&Neighbours::default(), &registry).len(), 1);
+
+    // glass hides faces between panes and against stone
+    assert_eq!(Mesh::translucent(&chunk, &Neighbours::default(), &registry).quads(), 9);
+}
+
+#[test]
+fn sorts_quads_back_to_front() {
+    let registry  = BlockRegistry::builtin();
+    let mut chunk = Chunk::empty(0, 0, 0);
+
+    for x in [0, 4, 8] {
+        chunk.set(x, 4, 4, block("glass"));
+    }
+
+    let mut mesh = Mesh::translucent(&chunk, &Neighbours::default(), &registry);
+    let indices  = mesh.indices.clone();
+
+    // distance from eye to quad centers in draw order
+    let distances = |mesh: &Mesh, eye: [f32; 3]| {
+        mesh.indices
+            .chunks_exact(6)
+            .map(|quad| {
+                let base = *quad.iter().min().unwrap() as usize;
+                let pos  = mesh.vertices[base..base + 4].iter().map(|vertex| vertex.pos());
+
+                let center = pos.fold([0.0; 3], |sum, pos| [sum[0] + pos[0], sum[1] + pos[1], sum[2] + pos[2]]);
+                (0..3).map(|axis| (center[axis] / 4.0 - eye[axis]).powi(2)).sum::<f32>()
+            }).collect::<Vec<_>>()
+    };
+
+    for eye in [[-10.0, 4.5, 4.5], [20.0, 8.0, 0.0]] {
+        mesh.sort_back_to_front(eye);
+
+        assert!(distances(&mesh, eye).windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+
+    // same triangles, reordered
+    let (mut sorted, mut original) = (mesh.indices.clone(), indices);
+    sorted.sort_unstable();
+    original.sort_unstable();
+
+    assert_eq!(sorted, original);
+}
+
+#[test]
+fn different_light_splits() {
+    let mut chunk = Chunk::empty(0, 0, 0);
+    fill(&mut chunk, [2, 5, 3], [6, 6, 7], block("dirt"));
+
+    // one lit block above the box
+    chunk.set_light(3, 6, 4, Channel::Sky, 10);
+
+    let mesh = greedy(&chunk, &Neighbours::default());
+    assert_eq!(mesh.quads(), 10);
+
+    // faces take the light in front of them
+    let lit = mesh.vertices.iter().filter(|vertex| vertex.light() == 10 << 4).count();
+    assert_eq!(lit, 4);
+
+    // instances light each face separately
+    let instances = chunk.instances(&Neighbours::default(), &BlockRegistry::builtin());
+    let lit       = instances.iter().filter(|instance| instance.light(4) == 10 << 4).count();
+
+    assert_eq!(lit, 1);
+}
+
+#[test]
+fn corner_occlusion() {
+    assert_eq!(mesh::occlusion(false, false, false), 3);
+    assert_eq!(mesh::occlusion(true, false, false), 2);
+    assert_eq!(mesh::occlusion(false, false, true), 2);
+    assert_eq!(mesh::occlusion(false, true, true), 1);
+
+    // two sides hide the corner whatever is between them
+    assert_eq!(mesh::occlusion(true, true, false), 0);
+    assert_eq!(mesh::occlusion(true, true, true), 0);
+}
+
+#[test]
+fn corners_darken_beside_blocks() {
+    let registry   = BlockRegistry::builtin();
+    let neighbours = Neighbours::default();
+    let mut chunk  = Chunk::empty(0, 0, 0);
+
+    chunk.set(1, 0, 1, block("dirt"));
+    assert_eq!(chunk.face_ao([1, 0, 1], 4, &neighbours, &registry), [3; 4]);
+
+    // wall along the back edge of the top face
+    chunk.set(1, 1, 0, block("stone"));
+    assert_eq!(chunk.face_ao([1, 0, 1], 4, &neighbours, &registry), [3, 3, 2, 2]);
+
+    // second wall on the left closes the back left corner
+    chunk.set(0, 1, 1, block("stone"));
+    assert_eq!(chunk.face_ao([1, 0, 1], 4, &neighbours, &registry), [2, 3, 0, 2]);
+
+    // transparent blocks cast no shadow
+    chunk.set(0, 1, 1, block("glass"));
+    assert_eq!(chunk.face_ao([1, 0, 1], 4, &neighbours, &registry), [3, 3, 2, 2]);
+
+    // instances carry the same corners
+    let instances = chunk.instances(&neighbours, &registry);
+    assert!(instances.iter().any(|instance| (0..4).map(|corner| instance.ao(4, corner)).eq([3, 3, 2, 2])));
+
+    // and so do mesh vertices
+    let mesh = greedy(&chunk, &neighbours);
+    let dark = mesh.vertices.iter().filter(|vertex| vertex.ao() == 2 && vertex.pos()[1] == 1.0).count();
+
+    assert!(dark >= 2);
+}
+
+#[test]
+fn different_occlusion_splits() {
+    // block beside the top edge of a box
+    let shaded = |name| {
+        let mut chunk = Chunk::empty(0, 0, 0);
+        fill(&mut chunk, [2, 5, 3], [6, 6, 7], block("dirt"));
+        chunk.set(1, 6, 4, block(name));
+
+        greedy(&chunk, &Neighbours::default()).quads()
+    };
+
+    // blended glass is meshed separately and casts no shadow
+    assert_eq!(shaded("glass"), 6);
+
+    // stone splits the darkened top and left faces of the box
+    assert!(shaded("stone") > 6 + 6);
+}
+
+#[test]
+fn quads_split_along_brighter_diagonal() {
+    // triangles of top face of block at (1, 0, 1), by corner index
+    let triangles = |occluder: [usize; 3]| {
+        let mut chunk = Chunk::empty(0, 0, 0);
+        chunk.set(1, 0, 1, block("dirt"));
+        chunk.set(occluder[0], occluder[1], occluder[2], block("stone"));
+
+        let mesh = greedy(&chunk, &Neighbours::default());
+
+        mesh.indices
+            .chunks_exact(6)
+            .find(|quad| {
+                quad.iter().all(|&idx| {
+                    let pos = mesh.vertices[idx as usize].pos();
+                    pos[1] == 1.0 && (1.0..=2.0).contains(&pos[0]) && (1.0..=2.0).contains(&pos[2])
+                })
+            })
+            .map(|quad| {
+                let base = *quad.iter().min().unwrap();
+                quad.iter().map(|idx| idx - base).collect::<Vec<_>>()
+            })
+            .unwrap()
+    };
+
+    // dark corner away from the default diagonal keeps it
+    let kept = triangles([2, 1, 0]);
+    assert!(kept.chunks(3).all(|tri| tri.contains(&1) && tri.contains(&2)));
+
+    // dark corner on the default diagonal turns the quad
+    let turned = triangles([2, 1, 2]);
+    assert!(turned.chunks(3).all(|tri| tri.contains(&0) && tri.contains(&3)));
+}
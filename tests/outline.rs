@@ -0,0 +1,23 @@
+use blocks::outline::{outline_vertices, OUTLINE_VERTICES};
+
+#[test]
+fn outline_encloses_block() {
+    let vertices = outline_vertices([-3, 5, 2]);
+    let data     = bytemuck::cast_slice::<_, [f32; 4]>(&vertices);
+
+    assert_eq!(vertices.len() as u32, OUTLINE_VERTICES);
+
+    // every corner sits just outside the block
+    for pos in data {
+        for (coord, min) in pos[..3].iter().zip([-3.0, 5.0, 2.0]) {
+            let edge = (coord - min).abs().min((coord - min - 1.0).abs());
+            assert!(edge > 0.0 && edge < 0.01);
+        }
+    }
+
+    // each edge spans one block
+    for line in data.chunks(2) {
+        let length = (0..3).map(|axis| (line[0][axis] - line[1][axis]).abs()).sum::<f32>();
+        assert!((length - 1.004).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,123 @@
+use blocks::block::Block;
+use blocks::player::Player;
+use blocks::seed::WorldSeed;
+use blocks::world::World;
+
+use cgmath::{Point3, Vector3, Zero};
+
+// simulation step
+const DT: f32 = 1.0 / 60.0;
+
+// world with flat floor at y = 0
+fn flat_world() -> World {
+    let mut world = World::new(WorldSeed::new(1));
+    world.load(0, 0, 0);
+
+    let dirt = world.registry().id("dirt").unwrap();
+
+    for (x, y, z) in itertools::iproduct!(0..16, 0..16, 0..16) {
+        world.set_block(x, y, z, if y == 0 { dirt } else { Block::AIR });
+    }
+
+    world
+}
+
+// walk for seconds
+fn walk(player: &mut Player, world: &World, direction: Vector3<f32>, jump: bool, secs: f32) {
+    for _ in 0..(secs / DT) as usize {
+        player.walk(world, direction, jump, false, DT);
+    }
+}
+
+#[test]
+fn falls_onto_floor() {
+    let world      = flat_world();
+    let mut player = Player::new(Point3::new(8.5, 6.0, 8.5));
+
+    walk(&mut player, &world, Vector3::zero(), false, 2.0);
+
+    assert!(player.on_ground());
+    assert!((player.pos().y - 1.0).abs() < 1e-3);
+    assert_eq!(player.velocity().y, 0.0);
+}
+
+#[test]
+fn blocked_by_wall() {
+    let mut world = flat_world();
+    let dirt      = world.registry().id("dirt").unwrap();
+
+    // two high wall
+    for (y, z) in itertools::iproduct!(1..3, 0..16) {
+        world.set_block(11, y, z, dirt);
+    }
+
+    let mut player = Player::new(Point3::new(8.5, 1.0, 8.5));
+    walk(&mut player, &world, Vector3::unit_x(), false, 2.0);
+
+    assert!(player.bounds().max.x <= 11.0 + 1e-3);
+    assert!(player.bounds().max.x > 10.9);
+    assert!((player.pos().y - 1.0).abs() < 1e-3);
+}
+
+#[test]
+fn steps_onto_block() {
+    let mut world = flat_world();
+    let dirt      = world.registry().id("dirt").unwrap();
+
+    // single block ledge
+    for (x, z) in itertools::iproduct!(11..16, 0..16) {
+        world.set_block(x, 1, z, dirt);
+    }
+
+    let mut player = Player::new(Point3::new(8.5, 1.0, 8.5));
+    walk(&mut player, &world, Vector3::unit_x(), false, 1.5);
+
+    assert!(player.pos().x > 11.5);
+    assert!((player.pos().y - 2.0).abs() < 1e-3);
+}
+
+#[test]
+fn jumps_above_block() {
+    let world      = flat_world();
+    let mut player = Player::new(Point3::new(8.5, 1.0, 8.5));
+
+    walk(&mut player, &world, Vector3::zero(), false, 0.1);
+
+    // highest point of jump
+    let mut peak = player.pos().y;
+
+    for frame in 0..60 {
+        player.walk(&world, Vector3::zero(), frame == 0, false, DT);
+        peak = peak.max(player.pos().y);
+    }
+
+    assert!(peak > 2.0);
+    assert!(player.on_ground());
+}
+
+#[test]
+fn hits_ceiling() {
+    let mut world = flat_world();
+    let dirt      = world.registry().id("dirt").unwrap();
+
+    world.set_block(8, 3, 8, dirt);
+
+    let mut player = Player::new(Point3::new(8.5, 1.0, 8.5));
+    walk(&mut player, &world, Vector3::zero(), false, 0.1);
+
+    for _ in 0..30 {
+        player.walk(&world, Vector3::zero(), true, false, DT);
+        assert!(player.bounds().max.y <= 3.0 + 1e-3);
+    }
+}
+
+#[test]
+fn waits_for_unloaded_chunks() {
+    let world      = World::new(WorldSeed::new(1));
+    let mut player = Player::new(Point3::new(8.5, 20.0, 8.5));
+
+    walk(&mut player, &world, Vector3::unit_x(), false, 1.0);
+
+    // unloaded chunks are solid
+    assert_eq!(player.pos(), Point3::new(8.5, 20.0, 8.5));
+}
@@ -0,0 +1,166 @@
+use blocks::aabb::Aabb;
+use blocks::block::Block;
+use blocks::camera::Camera;
+use blocks::player::Player;
+use blocks::raycast::{raycast, RayHit};
+use blocks::seed::WorldSeed;
+use blocks::world::World;
+
+use cgmath::{Point3, Vector3, Rad};
+
+use std::f32::consts::FRAC_PI_2;
+
+// cast against a single block
+fn cast_at(block: [i32; 3], origin: [f32; 3], direction: [f32; 3], max_distance: f32) -> Option<RayHit> {
+    raycast(
+        Point3::from(origin),
+        Vector3::from(direction),
+        max_distance,
+        |pos| pos == block,
+    )
+}
+
+// world with cleared chunk
+fn empty_world() -> World {
+    let mut world = World::new(WorldSeed::new(1));
+    world.load(0, 0, 0);
+
+    for (x, y, z) in itertools::iproduct!(0..16, 0..16, 0..16) {
+        world.set_block(x, y, z, Block::AIR);
+    }
+
+    world
+}
+
+#[test]
+fn hits_along_axes() {
+    let hit = cast_at([5, 0, 0], [0.5, 0.5, 0.5], [1.0, 0.0, 0.0], 10.0).unwrap();
+
+    assert_eq!(hit.pos, [5, 0, 0]);
+    assert_eq!(hit.normal, [-1, 0, 0]);
+    assert!((hit.distance - 4.5).abs() < 1e-5);
+    assert_eq!(hit.adjacent(), [4, 0, 0]);
+
+    let hit = cast_at([0, -3, 0], [0.5, 0.5, 0.5], [0.0, -1.0, 0.0], 10.0).unwrap();
+
+    assert_eq!(hit.normal, [0, 1, 0]);
+    assert!((hit.distance - 2.5).abs() < 1e-5);
+}
+
+#[test]
+fn hits_negative_coordinates() {
+    let hit = cast_at([-4, 2, -7], [0.5, 2.5, 0.5], [-4.0, 0.0, -7.0], 20.0).unwrap();
+
+    assert_eq!(hit.pos, [-4, 2, -7]);
+}
+
+#[test]
+fn hits_diagonal() {
+    let hit = cast_at([3, 3, 3], [0.5, 0.5, 0.5], [1.0, 1.0, 1.0], 10.0).unwrap();
+
+    assert_eq!(hit.pos, [3, 3, 3]);
+    assert_ne!(hit.normal, [0, 0, 0]);
+    assert!((hit.distance - 2.5 * 3f32.sqrt()).abs() < 1e-4);
+}
+
+#[test]
+fn stops_at_max_distance() {
+    assert!(cast_at([5, 0, 0], [0.5, 0.5, 0.5], [1.0, 0.0, 0.0], 4.0).is_none());
+    assert!(cast_at([5, 0, 0], [0.5, 0.5, 0.5], [1.0, 0.0, 0.0], 5.0).is_some());
+}
+
+#[test]
+fn starts_inside_block() {
+    let hit = cast_at([0, 0, 0], [0.5, 0.5, 0.5], [1.0, 0.0, 0.0], 10.0).unwrap();
+
+    assert_eq!(hit.normal, [0, 0, 0]);
+    assert_eq!(hit.distance, 0.0);
+}
+
+#[test]
+fn zero_direction_misses() {
+    assert!(cast_at([0, 0, 0], [0.5, 0.5, 0.5], [0.0, 0.0, 0.0], 10.0).is_none());
+}
+
+#[test]
+fn world_raycast() {
+    let mut world = empty_world();
+    let dirt      = world.registry().id("dirt").unwrap();
+
+    world.set_block(8, 0, 8, dirt);
+    world.set_block(8, 0, 12, dirt);
+
+    // nearest block wins
+    let hit = world.raycast(Point3::new(8.5, 0.5, 2.5), Vector3::unit_z(), 20.0).unwrap();
+
+    assert_eq!(hit.pos, [8, 0, 8]);
+    assert_eq!(hit.normal, [0, 0, -1]);
+
+    // air is passed through
+    world.set_block(8, 0, 8, Block::AIR);
+
+    let hit = world.raycast(Point3::new(8.5, 0.5, 2.5), Vector3::unit_z(), 20.0).unwrap();
+    assert_eq!(hit.pos, [8, 0, 12]);
+
+    // unloaded chunks are empty
+    assert!(world.raycast(Point3::new(8.5, 0.5, 2.5), -Vector3::unit_z(), 20.0).is_none());
+}
+
+#[test]
+fn camera_raycast() {
+    let mut world = empty_world();
+    let dirt      = world.registry().id("dirt").unwrap();
+
+    world.set_block(4, 0, 4, dirt);
+
+    // looking straight down
+    let camera = Camera::new(Point3::new(4.5, 6.0, 4.5), Rad(0.0), Rad(-FRAC_PI_2));
+    let hit    = world.raycast(camera.pos, camera.direction(), 10.0).unwrap();
+
+    assert_eq!(hit.pos, [4, 0, 4]);
+    assert_eq!(hit.normal, [0, 1, 0]);
+    assert_eq!(hit.adjacent(), [4, 1, 4]);
+}
+
+#[test]
+fn break_and_place() {
+    let mut world = empty_world();
+    let dirt      = world.registry().id("dirt").unwrap();
+
+    world.set_block(8, 0, 8, dirt);
+
+    // player standing far away
+    let player = Aabb::block([0, 20, 0]);
+    let hit    = world.raycast(Point3::new(8.5, 0.5, 2.5), Vector3::unit_z(), 20.0).unwrap();
+
+    assert!(world.place_block(&hit, dirt, &player));
+    assert_eq!(world.get_block(8, 0, 7), Some(dirt));
+
+    // occupied face
+    assert!(!world.place_block(&hit, dirt, &player));
+
+    let hit = world.raycast(Point3::new(8.5, 0.5, 2.5), Vector3::unit_z(), 20.0).unwrap();
+
+    assert!(world.break_block(&hit));
+    assert_eq!(world.get_block(8, 0, 7), Some(Block::AIR));
+}
+
+#[test]
+fn place_outside_player() {
+    let mut world = empty_world();
+    let dirt      = world.registry().id("dirt").unwrap();
+
+    world.set_block(4, 0, 4, dirt);
+
+    // standing on the block
+    let player = Player::new(Point3::new(4.5, 1.0, 4.5));
+    let camera = Camera::new(player.eye(), Rad(0.0), Rad(-FRAC_PI_2));
+    let hit    = world.raycast(camera.pos, camera.direction(), 10.0).unwrap();
+
+    assert!(!world.place_block(&hit, dirt, &player.bounds()));
+    assert_eq!(world.get_block(4, 1, 4), Some(Block::AIR));
+
+    // after stepping aside
+    let player = Player::new(Point3::new(6.5, 1.0, 4.5));
+    assert!(world.place_block(&hit, dirt, &player.bounds()));
+}
@@ -0,0 +1,65 @@
+use blocks::chunk::Chunk;
+use blocks::options::Options;
+use blocks::seed::WorldSeed;
+use blocks::world::World;
+
+mod common;
+
+use common::{generator, same_blocks};
+
+#[test]
+fn same_seed_same_chunk() {
+    for (x, y, z) in [(0, 0, 0), (3, -1, -2), (-7, 1, 11)] {
+        let a = Chunk::new(x, y, z, &generator(1234));
+        let b = Chunk::new(x, y, z, &generator(1234));
+
+        assert!(same_blocks(&a, &b), "chunk ({}, {}, {}) differs", x, y, z);
+    }
+}
+
+#[test]
+fn different_seed_different_chunk() {
+    let a = Chunk::new(0, 0, 0, &generator(1));
+    let b = Chunk::new(0, 0, 0, &generator(2));
+
+    assert!(!same_blocks(&a, &b));
+}
+
+#[test]
+fn same_seed_same_world() {
+    let mut a = World::new(WorldSeed::new(42));
+    let mut b = World::new(WorldSeed::new(42));
+
+    a.load(5, 0, -3);
+    b.load(5, 0, -3);
+
+    assert!(same_blocks(a.chunk(5, 0, -3).unwrap(), b.chunk(5, 0, -3).unwrap()));
+}
+
+#[test]
+fn parse_seed() {
+    assert_eq!("42".parse::<WorldSeed>().unwrap(), WorldSeed::new(42));
+    assert_eq!("-1".parse::<WorldSeed>().unwrap(), WorldSeed::new(u64::MAX));
+
+    // text seeds hash stably
+    assert_eq!("blocks".parse::<WorldSeed>().unwrap(), "blocks".parse::<WorldSeed>().unwrap());
+    assert_ne!("blocks".parse::<WorldSeed>().unwrap(), "other".parse::<WorldSeed>().unwrap());
+}
+
+#[test]
+fn seed_option() {
+    assert_eq!(Options::default().seed, None);
+    assert_eq!(Options::from_pairs([("seed", "42")]).seed, Some(WorldSeed::new(42)));
+
+    // flags without value are ignored
+    assert_eq!(Options::from_pairs([("seed", ""), ("world", "")]).seed, None);
+    assert_eq!(Options::from_pairs([("world", "")]).world, None);
+}
+
+#[test]
+fn derived_streams_differ() {
+    let seed = WorldSeed::new(7);
+
+    assert_eq!(seed.derive("trees"), seed.derive("trees"));
+    assert_ne!(seed.derive("trees"), seed.derive("ores"));
+}
@@ -0,0 +1,98 @@
+use blocks::seed::WorldSeed;
+use blocks::sky::{WorldTime, MIN_DAYLIGHT};
+use blocks::world::World;
+
+// compare with rounding slack
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-4
+}
+
+// clock at time of day with a hundred second day
+fn at(day_time: f32) -> WorldTime {
+    WorldTime::new(day_time, 100.0).unwrap()
+}
+
+#[test]
+fn clock_advances_and_wraps() {
+    let mut time = at(0.9);
+
+    // a fifth of a hundred second day
+    time.advance(20.0);
+    assert!(close(time.day_time(), 0.1));
+
+    time.set_day_time(-0.25);
+    assert!(close(time.day_time(), 0.75));
+
+    // bad days are refused
+    assert!(WorldTime::new(0.5, 0.0).is_err());
+    assert!(WorldTime::new(f32::NAN, 100.0).is_err());
+}
+
+#[test]
+fn clock_pauses_and_changes_speed() {
+    let mut time = at(0.5);
+
+    time.set_paused(true);
+    time.advance(50.0);
+
+    assert!(time.paused());
+    assert!(close(time.day_time(), 0.5));
+
+    // twice as fast once resumed
+    time.set_paused(false);
+    time.set_speed(2.0).unwrap();
+    time.advance(10.0);
+
+    assert!(close(time.day_time(), 0.7));
+
+    // running backwards is refused
+    assert!(time.set_speed(-1.0).is_err());
+    assert_eq!(time.speed(), 2.0);
+}
+
+#[test]
+fn sun_rises_and_sets() {
+    // high at noon, low at midnight
+    assert!(at(0.5).sun_direction().y > 0.9);
+    assert!(at(0.0).sun_direction().y < -0.9);
+
+    // rising along x, setting on the other side
+    let (sunrise, sunset) = (at(0.25).sun_direction(), at(0.75).sun_direction());
+
+    assert!(sunrise.y.abs() < 1e-4 && sunrise.x > 0.9);
+    assert!(sunset.y.abs() < 1e-4 && sunset.x < -0.9);
+}
+
+#[test]
+fn daylight_scales_sky_light() {
+    assert_eq!(at(0.5).daylight(), 1.0);
+    assert_eq!(at(0.0).daylight(), MIN_DAYLIGHT);
+
+    // brightens through sunrise
+    let morning = [0.24, 0.25, 0.26, 0.27].map(|day_time| at(day_time).daylight());
+    assert!(morning.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn sky_darkens_at_night() {
+    let (noon_zenith, noon_horizon) = at(0.5).sky_colours();
+    let (night_zenith, _)           = at(0.0).sky_colours();
+
+    assert_eq!(noon_zenith, [0.1, 0.4, 0.7]);
+    assert!(night_zenith.iter().zip(noon_zenith).all(|(night, noon)| *night < noon));
+
+    // horizon reddens around sunset
+    let (_, sunset_horizon) = at(0.75).sky_colours();
+
+    assert!(sunset_horizon[0] > sunset_horizon[2]);
+    assert!(noon_horizon[0] < noon_horizon[2]);
+}
+
+#[test]
+fn world_ticks_clock() {
+    let mut world = World::new(WorldSeed::new(1));
+    world.set_time(WorldTime::new(0.5, 10.0).unwrap());
+
+    world.tick(1.0);
+    assert!(close(world.time().day_time(), 0.6));
+}
@@ -0,0 +1,394 @@
+use blocks::block::{Block, BlockRegistry};
+use blocks::chunk::Chunk;
+use blocks::decorator::Decorations;
+use blocks::generator::{Generator, Caves};
+use blocks::region::RegionStorage;
+use blocks::seed::WorldSeed;
+use blocks::storage::{encode_chunk, decode_chunk, export_world, import_world, ChunkStorage, CHUNK_VERSION};
+use blocks::world::{ViewDistance, World};
+
+use std::fs;
+use std::sync::Arc;
+
+mod common;
+
+use common::{same_blocks, temp_dir};
+
+// generated chunk with edits
+fn edited_chunk(registry: &BlockRegistry) -> Chunk {
+    let generator = Generator::new(WorldSeed::new(5), registry).unwrap();
+    let mut chunk = Chunk::new(-3, 0, 7, &generator);
+
+    chunk.set(0, 0, 0, Block::AIR);
+    chunk.set(15, 15, 15, registry.id("grass").unwrap());
+    chunk.set_decorated((-1, 0, 1));
+
+    chunk
+}
+
+#[test]
+fn chunk_round_trip() {
+    let registry = BlockRegistry::builtin();
+    let chunk    = edited_chunk(&registry);
+
+    let data    = encode_chunk(&chunk, &registry).unwrap();
+    let decoded = decode_chunk(chunk.pos(), &data, &registry).unwrap();
+
+    assert_eq!(decoded.pos(), (-3, 0, 7));
+    assert!(same_blocks(&chunk, &decoded));
+    assert!(!decoded.modified());
+
+    // neighbour structures already placed
+    assert!(decoded.decorated((-1, 0, 1)));
+    assert!(!decoded.decorated((1, 0, -1)));
+
+    // palette and compression keep chunks small
+    assert!(data.len() < 2048);
+}
+
+#[test]
+fn chunk_palette_by_name() {
+    let registry = BlockRegistry::builtin();
+    let data     = encode_chunk(&edited_chunk(&registry), &registry).unwrap();
+
+    // same names with different ids
+    let reordered = BlockRegistry::from_json(r#"[
+        { "name": "grass", "textures": { "all": [4, 3] } },
+        { "name": "dirt",  "textures": { "all": [0, 2] } }
+    ]"#).unwrap();
+
+    let decoded = decode_chunk((-3, 0, 7), &data, &reordered).unwrap();
+    assert_eq!(decoded.block(15, 15, 15), reordered.id("grass").unwrap());
+
+    // missing names become air
+    let partial = BlockRegistry::from_json(r#"[
+        { "name": "dirt", "textures": { "all": [0, 2] } }
+    ]"#).unwrap();
+
+    let decoded = decode_chunk((-3, 0, 7), &data, &partial).unwrap();
+    assert_eq!(decoded.block(15, 15, 15), Block::AIR);
+}
+
+#[test]
+fn chunk_rejects_bad_data() {
+    let registry = BlockRegistry::builtin();
+    let data     = encode_chunk(&edited_chunk(&registry), &registry).unwrap();
+
+    // future version
+    let mut future = data.clone();
+    future[..2].copy_from_slice(&(CHUNK_VERSION + 1).to_le_bytes());
+
+    assert!(decode_chunk((0, 0, 0), &future, &registry).is_err());
+
+    // truncated
+    assert!(decode_chunk((0, 0, 0), &data[..data.len() / 2], &registry).is_err());
+    assert!(decode_chunk((0, 0, 0), &[], &registry).is_err());
+}
+
+#[test]
+fn region_round_trip() {
+    let dir     = temp_dir("region");
+    let storage = RegionStorage::open(&dir).unwrap();
+
+    // same region, different region and negative coordinates
+    let chunks = [((0, 0, 0), vec![1, 2, 3]), ((15, 3, 5), vec![4]), ((-1, -2, -40), vec![5, 6])];
+
+    for (pos, data) in &chunks {
+        storage.write(*pos, data).unwrap();
+    }
+
+    // overwrite keeps other slots
+    storage.write((0, 0, 0), &[7, 8]).unwrap();
+
+    assert_eq!(storage.read((0, 0, 0)).unwrap(), Some(vec![7, 8]));
+    assert_eq!(storage.read((15, 3, 5)).unwrap(), Some(vec![4]));
+    assert_eq!(storage.read((-1, -2, -40)).unwrap(), Some(vec![5, 6]));
+    assert_eq!(storage.read((0, 1, 0)).unwrap(), None);
+    assert_eq!(storage.read((100, 0, 100)).unwrap(), None);
+
+    assert_eq!(fs::read_dir(dir.join("region")).unwrap().count(), 2);
+
+    // slots map back to chunk positions
+    let mut entries = storage.entries().unwrap();
+    entries.sort();
+
+    assert_eq!(entries, vec![((-1, -2, -40), vec![5, 6]), ((0, 0, 0), vec![7, 8]), ((15, 3, 5), vec![4])]);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn region_batches_and_compacts() {
+    let dir     = temp_dir("compact");
+    let storage = RegionStorage::open(&dir).unwrap();
+
+    // several chunks across two regions at once
+    let chunks = vec![((0, 0, 0), vec![1; 100]), ((1, 0, 0), vec![2; 50]), ((-1, 0, 0), vec![3; 10])];
+    storage.write_chunks(&chunks).unwrap();
+
+    // rewriting one chunk keeps file near its live size
+    for len in 1..50 {
+        storage.write((0, 0, 0), &vec![len as u8; len * 10]).unwrap();
+    }
+
+    // header plus at most twice as much replaced data as live data
+    let path = dir.join("region").join("r.0.0.0.bin");
+    assert!(fs::metadata(path).unwrap().len() <= 4 + 2 + 4096 * 8 + 3 * (490 + 50));
+
+    assert_eq!(storage.read((0, 0, 0)).unwrap(), Some(vec![49; 490]));
+    assert_eq!(storage.read((1, 0, 0)).unwrap(), Some(vec![2; 50]));
+    assert_eq!(storage.read((-1, 0, 0)).unwrap(), Some(vec![3; 10]));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn region_rejects_bad_header() {
+    let dir     = temp_dir("header");
+    let storage = RegionStorage::open(&dir).unwrap();
+
+    storage.write((0, 0, 0), &[1]).unwrap();
+
+    let path = dir.join("region").join("r.0.0.0.bin");
+    let mut data = fs::read(&path).unwrap();
+
+    // future version
+    data[4] = 0xff;
+    fs::write(&path, &data).unwrap();
+
+    assert!(storage.read((0, 0, 0)).is_err());
+
+    // wrong signature
+    fs::write(&path, b"nope").unwrap();
+    assert!(storage.read((0, 0, 0)).is_err());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn world_saves_changes() {
+    let dir     = temp_dir("world");
+    let storage = Arc::new(RegionStorage::open(&dir).unwrap());
+
+    storage.write_seed(WorldSeed::new(11)).unwrap();
+
+    let mut world = World::new(WorldSeed::new(11));
+    world.set_storage(storage.clone());
+    world.load(2, 0, -2);
+
+    world.set_block(40, 0, -20, Block::AIR);
+    world.set_block(40, 1, -20, world.registry().id("dirt").unwrap());
+
+    let expected = world.get_block(40, 1, -20);
+
+    // unloading saves changes
+    world.unload(2, 0, -2);
+    assert!(storage.read((2, 0, -2)).unwrap().is_some());
+
+    // untouched chunks are not saved
+    world.load(3, 0, -2);
+    world.unload(3, 0, -2);
+    assert!(storage.read((3, 0, -2)).unwrap().is_none());
+
+    // reopened world loads saved chunk
+    let storage = Arc::new(RegionStorage::open(&dir).unwrap());
+    let seed    = storage.read_seed().unwrap().unwrap();
+
+    let mut world = World::new(seed);
+    world.set_storage(storage);
+    world.load(2, 0, -2);
+
+    assert_eq!(seed, WorldSeed::new(11));
+    assert_eq!(world.get_block(40, 0, -20), Some(Block::AIR));
+    assert_eq!(world.get_block(40, 1, -20), expected);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn world_save_all() {
+    let dir     = temp_dir("save");
+    let storage = Arc::new(RegionStorage::open(&dir).unwrap());
+
+    let mut world = World::new(WorldSeed::new(11));
+    world.set_storage(storage.clone());
+    world.load(0, 0, 0);
+
+    // replace whatever generation placed
+    let block = match world.get_block(1, 1, 1) {
+        Some(Block::AIR) => world.registry().id("dirt").unwrap(),
+        _                => Block::AIR,
+    };
+
+    world.set_block(1, 1, 1, block);
+    world.save().unwrap();
+
+    assert!(storage.read((0, 0, 0)).unwrap().is_some());
+    assert!(!world.chunk(0, 0, 0).unwrap().modified());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn saving_packs_settled_chunks() {
+    let dir     = temp_dir("pack");
+    let storage = Arc::new(RegionStorage::open(&dir).unwrap());
+
+    let mut world = World::new(WorldSeed::new(1));
+    world.set_storage(storage);
+    world.set_caves(Caves::NONE).unwrap();
+    world.set_decorations(Decorations::NONE).unwrap();
+    world.load(0, -3, 0);
+
+    let stone  = world.get_block(0, -40, 0).unwrap();
+    let memory = world.chunk(0, -3, 0).unwrap().blocks().memory();
+
+    // dig and refill, leaving air in the palette
+    world.set_block(0, -40, 0, Block::AIR);
+    world.set_block(0, -40, 0, stone);
+
+    assert!(world.chunk(0, -3, 0).unwrap().blocks().memory() > memory);
+
+    // saving returns to a single block
+    world.save().unwrap();
+
+    let chunk = world.chunk(0, -3, 0).unwrap();
+
+    assert_eq!(chunk.blocks().single(), Some(stone));
+    assert_eq!(chunk.blocks().memory(), memory);
+    assert_eq!(world.stats().memory, chunk.memory());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn world_saves_evicted_chunks() {
+    let dir     = temp_dir("evict");
+    let storage = Arc::new(RegionStorage::open(&dir).unwrap());
+
+    let mut world = World::new(WorldSeed::new(11));
+    world.set_storage(storage.clone());
+    world.set_view_distance(ViewDistance { horizontal: 1, vertical: 1 });
+    world.refresh(0, 0, 0);
+    world.finish();
+
+    // replace whatever generation placed
+    let block = match world.get_block(1, 1, 1) {
+        Some(Block::AIR) => world.registry().id("dirt").unwrap(),
+        _                => Block::AIR,
+    };
+
+    world.set_block(1, 1, 1, block);
+
+    // walk away and back, changes survive eviction
+    for x in [0, 64, 128, 64, 0] {
+        world.refresh(x, 0, 0);
+        world.finish();
+    }
+
+    assert_eq!(world.get_block(1, 1, 1), Some(block));
+
+    // evicted chunks are written by save
+    world.refresh(128, 0, 0);
+    world.refresh(256, 0, 0);
+    world.save().unwrap();
+
+    assert!(world.chunk(0, 0, 0).is_none());
+
+    let data  = storage.read((0, 0, 0)).unwrap().unwrap();
+    let chunk = decode_chunk((0, 0, 0), &data, world.registry()).unwrap();
+
+    assert_eq!(chunk.block(1, 1, 1), block);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn export_round_trip() {
+    let (from_dir, to_dir) = (temp_dir("export"), temp_dir("import"));
+
+    let from = RegionStorage::open(&from_dir).unwrap();
+    let to   = RegionStorage::open(&to_dir).unwrap();
+
+    let registry = BlockRegistry::builtin();
+    let chunk    = encode_chunk(&edited_chunk(&registry), &registry).unwrap();
+
+    from.write_seed(WorldSeed::new(u64::MAX)).unwrap();
+    from.write((-3, 0, 7), &chunk).unwrap();
+    from.write((40, -5, -90), &[1, 2, 3]).unwrap();
+
+    let archive = export_world(&from).unwrap();
+    assert_eq!(import_world(&to, &archive).unwrap(), WorldSeed::new(u64::MAX));
+
+    // chunks copied byte for byte
+    let mut entries = to.entries().unwrap();
+    entries.sort();
+
+    assert_eq!(entries, vec![((-3, 0, 7), chunk), ((40, -5, -90), vec![1, 2, 3])]);
+    assert_eq!(to.read_seed().unwrap(), Some(WorldSeed::new(u64::MAX)));
+
+    fs::remove_dir_all(from_dir).unwrap();
+    fs::remove_dir_all(to_dir).unwrap();
+}
+
+#[test]
+fn import_rejects_bad_data() {
+    let (from_dir, to_dir) = (temp_dir("bad-export"), temp_dir("bad-import"));
+
+    let from    = RegionStorage::open(&from_dir).unwrap();
+    let storage = RegionStorage::open(&to_dir).unwrap();
+
+    from.write_seed(WorldSeed::new(1)).unwrap();
+    from.write((0, 0, 0), &[1, 2, 3]).unwrap();
+
+    let archive = export_world(&from).unwrap();
+
+    assert!(import_world(&storage, b"nope").is_err());
+    assert!(import_world(&storage, &archive[..archive.len() - 1]).is_err());
+
+    // future version
+    let mut future = archive.clone();
+    future[4] = 0xff;
+
+    assert!(import_world(&storage, &future).is_err());
+
+    // nothing written from rejected archives
+    assert!(storage.read_seed().unwrap().is_none());
+    assert!(storage.entries().unwrap().is_empty());
+
+    fs::remove_dir_all(from_dir).unwrap();
+    fs::remove_dir_all(to_dir).unwrap();
+}
+
+#[test]
+fn import_rejects_existing_world() {
+    let (from_dir, to_dir) = (temp_dir("existing-export"), temp_dir("existing-import"));
+
+    let from = RegionStorage::open(&from_dir).unwrap();
+    let to   = RegionStorage::open(&to_dir).unwrap();
+
+    from.write_seed(WorldSeed::new(1)).unwrap();
+    from.write((0, 0, 0), &[1, 2, 3]).unwrap();
+
+    let archive = export_world(&from).unwrap();
+
+    // importing twice would mix in chunks of a second seed
+    import_world(&to, &archive).unwrap();
+    assert!(import_world(&to, &archive).is_err());
+
+    // saved chunks without seed
+    fs::remove_file(to_dir.join("seed.txt")).unwrap();
+    assert!(import_world(&to, &archive).is_err());
+
+    // seed without chunks
+    let seeded_dir = temp_dir("existing-seed");
+    let seeded     = RegionStorage::open(&seeded_dir).unwrap();
+
+    seeded.write_seed(WorldSeed::new(2)).unwrap();
+    assert!(import_world(&seeded, &archive).is_err());
+    assert!(seeded.entries().unwrap().is_empty());
+
+    fs::remove_dir_all(from_dir).unwrap();
+    fs::remove_dir_all(to_dir).unwrap();
+    fs::remove_dir_all(seeded_dir).unwrap();
+}
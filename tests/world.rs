@@ -0,0 +1,289 @@
+use blocks::block::Block;
+use blocks::chunk::CHUNK_SIZE;
+use blocks::decorator::Decorations;
+use blocks::generator::Caves;
+use blocks::seed::WorldSeed;
+use blocks::world::{World, WorldHeight, Eviction, ViewDistance};
+
+mod common;
+
+use common::same_blocks;
+
+// small view keeping walks quick
+const DISTANCE: ViewDistance = ViewDistance { horizontal: 2, vertical: 1 };
+
+// view diameter in chunks
+const VIEW: usize = 2 * DISTANCE.horizontal as usize + 1;
+
+// view height in chunks
+const LAYERS: usize = 2 * DISTANCE.vertical as usize + 1;
+
+// chunks in view
+const LOADED: usize = VIEW * VIEW * LAYERS;
+
+// world with small view
+fn small_world(seed: u64) -> World {
+    let mut world = World::new(WorldSeed::new(seed));
+    world.set_view_distance(DISTANCE);
+
+    world
+}
+
+// refresh and wait for generation
+fn refresh(world: &mut World, pos_x: i32) {
+    world.refresh(pos_x, 0, 0);
+    world.finish();
+
+    // mesh finished chunks
+    world.refresh(pos_x, 0, 0);
+}
+
+// replace block with a different one
+fn toggle(world: &mut World, x: i32, y: i32, z: i32) {
+    let block = match world.get_block(x, y, z) {
+        Some(Block::AIR) => world.registry().id("dirt").unwrap(),
+        _                => Block::AIR,
+    };
+
+    assert!(world.set_block(x, y, z, block));
+}
+
+// refresh until world is centered on chunk
+fn walk_to(world: &mut World, chunk_x: i32) {
+    for _ in 0..chunk_x.unsigned_abs() + 1 {
+        refresh(world, chunk_x * CHUNK_SIZE as i32);
+    }
+}
+
+#[test]
+fn loads_view() {
+    let mut world = small_world(1);
+    refresh(&mut world, 0);
+
+    let stats = world.stats();
+
+    assert_eq!(stats.loaded, LOADED);
+    assert_eq!(stats.pending, 0);
+    assert_eq!(stats.generated, LOADED as u64);
+    assert_eq!(stats.evicted, 0);
+    assert!(stats.memory > 0);
+}
+
+#[test]
+fn default_view_distance() {
+    let world = World::new(WorldSeed::new(1));
+    assert_eq!(world.view_distance(), ViewDistance { horizontal: 8, vertical: 3 });
+}
+
+#[test]
+fn evicts_by_distance() {
+    let mut world = small_world(1);
+    walk_to(&mut world, 8);
+
+    let stats = world.stats();
+    let margin = 2 * world.eviction().margin as usize;
+
+    // only view and margin stay loaded
+    assert!(stats.loaded <= (VIEW + margin).pow(2) * (LAYERS + margin));
+    assert_eq!(stats.generated - stats.evicted, stats.loaded as u64);
+    assert!(stats.evicted > 0);
+
+    // far chunks reported for freeing
+    let unloaded = world.take_unloaded();
+
+    assert_eq!(unloaded.len() as u64, stats.evicted);
+    assert!(unloaded.iter().all(|pos| world.chunk(pos.0, pos.1, pos.2).is_none()));
+    assert!(world.take_unloaded().is_empty());
+}
+
+#[test]
+fn evicts_by_chunk_limit() {
+    let mut world = small_world(1);
+
+    world.set_eviction(Eviction {
+        max_chunks: Some(LOADED),
+        ..Default::default()
+    });
+
+    walk_to(&mut world, 6);
+
+    assert_eq!(world.stats().loaded, LOADED);
+}
+
+#[test]
+fn evicts_by_memory_limit() {
+    let mut world = small_world(1);
+    refresh(&mut world, 0);
+
+    // budget for the view only
+    let budget = world.stats().memory;
+
+    world.set_eviction(Eviction {
+        max_memory: Some(budget),
+        ..Default::default()
+    });
+
+    walk_to(&mut world, 6);
+
+    assert!(world.stats().memory <= budget);
+    assert!(world.stats().evicted > 0);
+}
+
+#[test]
+fn keeps_view_over_limit() {
+    let mut world = small_world(1);
+
+    world.set_eviction(Eviction {
+        max_chunks: Some(1),
+        ..Default::default()
+    });
+
+    refresh(&mut world, 0);
+
+    // chunks in view are never evicted
+    assert_eq!(world.stats().loaded, LOADED);
+}
+
+#[test]
+fn generates_in_background() {
+    let mut world = small_world(1);
+    world.refresh(0, 0, 0);
+
+    // requested without blocking
+    let stats = world.stats();
+    assert_eq!(stats.loaded + stats.pending, LOADED);
+
+    world.finish();
+
+    let stats = world.stats();
+    assert_eq!(stats.loaded, LOADED);
+    assert_eq!(stats.pending, 0);
+}
+
+#[test]
+fn background_matches_immediate() {
+    let mut world = small_world(3);
+    refresh(&mut world, 0);
+
+    let mut other = World::new(WorldSeed::new(3));
+
+    // neighbours add their structures
+    for (x, y, z) in itertools::iproduct!(0..3, -1..2, -2..1) {
+        other.load(x, y, z);
+    }
+
+    assert!(same_blocks(world.chunk(1, 0, -1).unwrap(), other.chunk(1, 0, -1).unwrap()));
+}
+
+#[test]
+fn get_and_set_block() {
+    let mut world = World::new(WorldSeed::new(1));
+    world.load(-1, -1, 0);
+
+    let dirt = world.registry().id("dirt").unwrap();
+
+    // negative coordinates map into chunk -1
+    assert!(world.set_block(-1, -3, 5, dirt));
+    assert_eq!(world.get_block(-1, -3, 5), Some(dirt));
+    assert_eq!(world.chunk(-1, -1, 0).unwrap().block(15, 13, 5), dirt);
+
+    assert!(world.set_block(-16, -16, 0, Block::AIR));
+    assert_eq!(world.get_block(-16, -16, 0), Some(Block::AIR));
+}
+
+#[test]
+fn missing_blocks() {
+    let mut world = World::new(WorldSeed::new(1));
+    let height    = WorldHeight::new(0, 32).unwrap();
+
+    world.set_height(height);
+    world.load(0, 0, 0);
+    world.load(0, 1, 0);
+
+    // outside world bounds
+    assert_eq!(world.get_block(0, -1, 0), None);
+    assert_eq!(world.get_block(0, 32, 0), None);
+    assert!(!world.set_block(0, -1, 0, Block::AIR));
+
+    // unloaded chunk
+    assert_eq!(world.get_block(CHUNK_SIZE as i32, 0, 0), None);
+    assert!(!world.set_block(CHUNK_SIZE as i32, 0, 0, Block::AIR));
+
+    // solid floor below bounds, open sky above
+    assert!(world.is_solid(0, -1, 0));
+    assert!(!world.is_solid(0, 32, 0));
+}
+
+#[test]
+fn loads_within_height() {
+    let mut world = small_world(1);
+
+    assert!(WorldHeight::new(0, 0).is_err());
+    assert!(WorldHeight::new(-8, 64).is_err());
+
+    world.set_height(WorldHeight::new(-16, 16).unwrap());
+    refresh(&mut world, 0);
+
+    // view clipped to two layers
+    assert_eq!(world.stats().loaded, VIEW * VIEW * 2);
+    assert!(world.visible().all(|(_, y, _)| y == -1 || y == 0));
+}
+
+#[test]
+fn uniform_sections_are_cheap() {
+    let mut world = World::new(WorldSeed::new(1));
+
+    // solid rock without caves or ores
+    world.set_caves(Caves::NONE).unwrap();
+    world.set_decorations(Decorations::NONE).unwrap();
+
+    // far above terrain and deep below it
+    world.load(0, 8, 0);
+    world.load(0, -3, 0);
+
+    let (sky, ground) = (world.chunk(0, 8, 0).unwrap(), world.chunk(0, -3, 0).unwrap());
+    let memory        = ground.memory();
+
+    assert!(sky.is_empty());
+    assert!(!ground.is_empty());
+    assert_eq!(sky.memory(), memory);
+
+    // second block type packs indices
+    assert!(world.set_block(0, -40, 0, Block::AIR));
+
+    let ground = world.chunk(0, -3, 0).unwrap();
+
+    assert!(ground.memory() > memory);
+    assert_eq!(world.stats().memory, world.chunk(0, 8, 0).unwrap().memory() + ground.memory());
+}
+
+#[test]
+fn set_block_remeshes_chunks() {
+    let mut world = small_world(1);
+    refresh(&mut world, 0);
+    world.take_meshes();
+
+    // inside chunk
+    toggle(&mut world, 5, 5, 5);
+    world.refresh(0, 0, 0);
+
+    let meshes = world.take_meshes();
+    assert_eq!(meshes.iter().map(|(pos, _)| *pos).collect::<Vec<_>>(), vec![(0, 0, 0)]);
+
+    // on corner of chunk
+    toggle(&mut world, 15, 0, 0);
+    world.refresh(0, 0, 0);
+
+    let mut meshed = world.take_meshes().into_iter().map(|(pos, _)| pos).collect::<Vec<_>>();
+    meshed.sort();
+
+    assert_eq!(meshed, vec![(0, -1, 0), (0, 0, -1), (0, 0, 0), (1, 0, 0)]);
+
+    // unchanged block
+    let block = world.get_block(15, 0, 0).unwrap();
+
+    world.set_block(15, 0, 0, block);
+    world.refresh(0, 0, 0);
+
+    assert!(world.take_meshes().is_empty());
+}